@@ -0,0 +1,251 @@
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+// 1 SILA is pegged to 1 US cent. Amounts travel over the wire as a whole number of SILA, so
+// this type only ever holds a non-negative integer count; dollars only appear when formatting
+// or parsing USD strings.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct SilaAmount(u64);
+
+#[derive(Debug, PartialEq)]
+pub enum AmountError {
+    Negative,
+    Zero,
+    Overflow,
+    Fractional,
+    Invalid(String),
+}
+
+impl std::fmt::Display for AmountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AmountError::Negative => write!(f, "SILA amounts cannot be negative"),
+            AmountError::Zero => write!(f, "SILA amount must be greater than zero"),
+            AmountError::Overflow => write!(f, "SILA amount is out of range"),
+            AmountError::Fractional => write!(f, "SILA amounts are whole cents"),
+            AmountError::Invalid(x) => write!(f, "invalid amount: {}", x),
+        }
+    }
+}
+
+impl std::error::Error for AmountError {}
+
+impl SilaAmount {
+    pub const ZERO: SilaAmount = SilaAmount(0);
+
+    pub fn from_sila(sila: i64) -> Result<Self, AmountError> {
+        if sila < 0 {
+            return Err(AmountError::Negative);
+        }
+        Ok(SilaAmount(sila as u64))
+    }
+
+    // for transaction amounts, where zero is as invalid as negative
+    pub fn positive(sila: i64) -> Result<Self, AmountError> {
+        match Self::from_sila(sila)? {
+            x if x.is_zero() => Err(AmountError::Zero),
+            x => Ok(x),
+        }
+    }
+
+    pub fn from_dollars(dollars: u64) -> Result<Self, AmountError> {
+        dollars
+            .checked_mul(100)
+            .map(SilaAmount)
+            .ok_or(AmountError::Overflow)
+    }
+
+    // accepts "$1,234.56", "1234.56", "12.5" and "12"; more than two decimal places is an error
+    pub fn from_usd_str(s: &str) -> Result<Self, AmountError> {
+        let trimmed = s.trim();
+        if trimmed.starts_with('-') {
+            return Err(AmountError::Negative);
+        }
+
+        let cleaned: String = trimmed
+            .trim_start_matches('$')
+            .chars()
+            .filter(|c| *c != ',')
+            .collect();
+
+        let (dollars, cents) = match cleaned.split_once('.') {
+            Some((d, c)) => (d, c),
+            None => (cleaned.as_str(), ""),
+        };
+
+        let valid = |x: &str| x.chars().all(|c| c.is_ascii_digit());
+        if (dollars.is_empty() && cents.is_empty()) || !valid(dollars) || !valid(cents) {
+            return Err(AmountError::Invalid(s.to_string()));
+        }
+        if cents.len() > 2 {
+            return Err(AmountError::Fractional);
+        }
+
+        let dollars: u64 = match dollars {
+            "" => 0,
+            x => x.parse().map_err(|_| AmountError::Overflow)?,
+        };
+        let cents: u64 = format!("{:0<2}", cents).parse().unwrap_or(0);
+
+        Self::from_dollars(dollars)?
+            .checked_add(SilaAmount(cents))
+            .ok_or(AmountError::Overflow)
+    }
+
+    pub fn sila(&self) -> u64 {
+        self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn checked_add(self, other: SilaAmount) -> Option<SilaAmount> {
+        self.0.checked_add(other.0).map(SilaAmount)
+    }
+
+    pub fn checked_sub(self, other: SilaAmount) -> Option<SilaAmount> {
+        self.0.checked_sub(other.0).map(SilaAmount)
+    }
+
+    pub fn checked_mul(self, factor: u64) -> Option<SilaAmount> {
+        self.0.checked_mul(factor).map(SilaAmount)
+    }
+
+    pub fn to_usd_string(&self) -> String {
+        let dollars = (self.0 / 100).to_string();
+        let mut grouped = String::new();
+
+        for (i, c) in dollars.chars().enumerate() {
+            if i > 0 && (dollars.len() - i).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(c);
+        }
+
+        format!("${}.{:02}", grouped, self.0 % 100)
+    }
+}
+
+impl std::fmt::Display for SilaAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for SilaAmount {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sila: i64 = s
+            .trim()
+            .parse()
+            .map_err(|_| AmountError::Invalid(s.to_string()))?;
+        Self::from_sila(sila)
+    }
+}
+
+impl From<u32> for SilaAmount {
+    fn from(sila: u32) -> Self {
+        SilaAmount(sila as u64)
+    }
+}
+
+impl TryFrom<i32> for SilaAmount {
+    type Error = AmountError;
+
+    fn try_from(sila: i32) -> Result<Self, Self::Error> {
+        Self::from_sila(sila as i64)
+    }
+}
+
+impl TryFrom<i64> for SilaAmount {
+    type Error = AmountError;
+
+    fn try_from(sila: i64) -> Result<Self, Self::Error> {
+        Self::from_sila(sila)
+    }
+}
+
+impl Serialize for SilaAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+struct SilaAmountVisitor;
+
+impl<'de> Visitor<'de> for SilaAmountVisitor {
+    type Value = SilaAmount;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a non-negative whole number of SILA")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(SilaAmount(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        SilaAmount::from_sila(v).map_err(E::custom)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        if v < 0.0 {
+            return Err(E::custom(AmountError::Negative));
+        }
+        if v.fract() != 0.0 {
+            return Err(E::custom(AmountError::Fractional));
+        }
+        if v > u64::MAX as f64 {
+            return Err(E::custom(AmountError::Overflow));
+        }
+        Ok(SilaAmount(v as u64))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for SilaAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(SilaAmountVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats_usd() {
+        assert_eq!(SilaAmount::from_usd_str("$1,234.56").unwrap().sila(), 123456);
+        assert_eq!(SilaAmount::from_usd_str("12.5").unwrap().sila(), 1250);
+        assert_eq!(SilaAmount::from_usd_str("12").unwrap().sila(), 1200);
+        assert_eq!(SilaAmount::from_usd_str("1.005"), Err(AmountError::Fractional));
+        assert_eq!(SilaAmount::from_usd_str("-1.00"), Err(AmountError::Negative));
+        assert!(SilaAmount::from_usd_str("12a").is_err());
+
+        assert_eq!(SilaAmount::from(123456789).to_usd_string(), "$1,234,567.89");
+        assert_eq!(SilaAmount::from(5).to_usd_string(), "$0.05");
+    }
+
+    #[test]
+    fn validates_on_construction() {
+        assert_eq!(SilaAmount::from_sila(-1), Err(AmountError::Negative));
+        assert_eq!(SilaAmount::positive(0), Err(AmountError::Zero));
+        assert_eq!(SilaAmount::from(u32::MAX).checked_mul(u64::MAX), None);
+        assert_eq!(SilaAmount::from(1).checked_sub(SilaAmount::from(2)), None);
+    }
+
+    #[test]
+    fn serde_matches_wire_format() {
+        let amount: SilaAmount = serde_json::from_str("1500").unwrap();
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "1500");
+        assert_eq!(serde_json::from_str::<SilaAmount>("1500.0").unwrap(), amount);
+        assert!(serde_json::from_str::<SilaAmount>("-5").is_err());
+        assert!(serde_json::from_str::<SilaAmount>("15.5").is_err());
+    }
+}
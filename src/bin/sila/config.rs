@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+// ~/.config/sila/config.toml, or the file named by --config / SILA_CONFIG:
+//
+//   default_profile = "sandbox"
+//
+//   [profiles.sandbox]
+//   environment = "sandbox"
+//   app_handle = "acme.silamoney.eth"
+//   app_address = "0x..."
+//   signer = { type = "local", app_key_env = "SILA_APP_KEY" }
+//
+//   [profiles.production]
+//   environment = "production"
+//   app_handle = "acme.silamoney.eth"
+//   app_address = "0x..."
+//   signer = { type = "remote", url = "https://signer.internal/sign" }
+
+#[derive(Deserialize, Default)]
+pub struct Config {
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct Profile {
+    pub environment: Option<String>,
+    pub gateway: Option<String>,
+    pub app_handle: Option<String>,
+    pub app_address: Option<String>,
+    pub signer: Option<SignerConfig>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SignerConfig {
+    Local {
+        app_key: Option<String>,
+        app_key_env: Option<String>,
+    },
+    Remote {
+        url: String,
+    },
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        env::var("HOME")
+            .ok()
+            .map(|x| Path::new(&x).join(".config").join("sila").join("config.toml"))
+    }
+
+    // A missing file is only an error when it was named explicitly.
+    pub fn load(path: Option<&Path>) -> Result<Config, Box<dyn std::error::Error + Sync + Send>> {
+        let (path, explicit) = match path {
+            Some(x) => (x.to_path_buf(), true),
+            None => match Config::default_path() {
+                Some(x) => (x, false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text),
+            Err(e) if !explicit && e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(Box::from(format!("could not read {}: {}", path.display(), e))),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Config, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(toml::from_str(text)?)
+    }
+
+    // With no profiles configured the tool runs from the SILA_* environment variables alone.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, Box<dyn std::error::Error + Sync + Send>> {
+        match name.or(self.default_profile.as_deref()) {
+            Some(x) => self
+                .profiles
+                .get(x)
+                .cloned()
+                .ok_or_else(|| Box::from(format!("no profile named {}", x))),
+            None => Ok(Profile::default()),
+        }
+    }
+}
+
+impl Profile {
+    // SILA_PARAMS is built from the environment on first use, so the profile is applied there
+    // before any request is made.
+    pub fn apply(&self) {
+        let vars = [
+            ("SILA_ENV", self.environment.as_ref().map(|x| x.to_uppercase())),
+            ("SILA_GATEWAY", self.gateway.clone()),
+            ("SILA_APP_HANDLE", self.app_handle.clone()),
+            ("SILA_APP_ADDRESS", self.app_address.clone()),
+        ];
+
+        for (name, value) in vars {
+            if let Some(x) = value {
+                env::set_var(name, x);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_profiles_and_signers() {
+        let config = Config::parse(
+            r#"
+            default_profile = "sandbox"
+
+            [profiles.sandbox]
+            environment = "sandbox"
+            app_handle = "acme"
+            signer = { type = "local", app_key_env = "ACME_KEY" }
+
+            [profiles.production]
+            environment = "production"
+            signer = { type = "remote", url = "https://signer.internal/sign" }
+            "#,
+        )
+        .unwrap();
+
+        let sandbox = config.profile(None).unwrap();
+        assert_eq!(sandbox.app_handle.as_deref(), Some("acme"));
+        assert!(matches!(
+            sandbox.signer,
+            Some(SignerConfig::Local { app_key_env: Some(ref x), .. }) if x == "ACME_KEY"
+        ));
+
+        let production = config.profile(Some("production")).unwrap();
+        assert!(matches!(production.signer, Some(SignerConfig::Remote { .. })));
+
+        assert!(config.profile(Some("staging")).is_err());
+    }
+}
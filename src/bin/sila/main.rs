@@ -0,0 +1,382 @@
+mod config;
+mod output;
+mod signer;
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::{Args, Parser, Subcommand};
+use futures::{StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use web3::types::H160;
+
+use silamoney::*;
+
+use config::Config;
+use output::OutputFormat;
+use signer::Signer;
+
+type Error = Box<dyn std::error::Error + Sync + Send>;
+
+#[derive(Parser)]
+#[command(name = "sila", about = "Operator tool for the Sila Banking API", version)]
+struct Cli {
+    /// Config file with profiles [default: ~/.config/sila/config.toml]
+    #[arg(long, global = true, env = "SILA_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Profile to use instead of the config's default_profile
+    #[arg(long, short, global = true, env = "SILA_PROFILE")]
+    profile: Option<String>,
+
+    /// Private key of the user being acted for, when signing locally
+    #[arg(long, global = true, env = "SILA_USER_KEY", hide_env_values = true)]
+    user_key: Option<String>,
+
+    #[arg(long, short, global = true, value_enum, default_value = "table")]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check whether a handle is available
+    CheckHandle { handle: String },
+    /// Show an entity's registration details
+    GetEntity { handle: String },
+    /// Submit an entity for KYC review
+    RequestKyc { handle: String },
+    /// Show an entity's KYC status
+    CheckKyc { handle: String },
+    /// Search transactions
+    Transactions(TransactionsArgs),
+    /// Issue SILA from a linked account
+    Issue(IssueArgs),
+    /// Redeem SILA to a linked account
+    Redeem(RedeemArgs),
+    /// Transfer SILA to another handle
+    Transfer(TransferArgs),
+    /// Cancel a queued or pending transaction
+    Cancel { handle: String, transaction_id: String },
+    /// Show the SILA balance of an address, or of a handle's registered wallet
+    Balance {
+        address: Option<H160>,
+        #[arg(long)]
+        handle: Option<String>,
+    },
+}
+
+#[derive(Args)]
+struct TransactionsArgs {
+    #[arg(long)]
+    handle: Option<String>,
+    #[arg(long)]
+    transaction_id: Option<String>,
+    #[arg(long)]
+    reference_id: Option<String>,
+    /// Repeatable; defaults to every status
+    #[arg(long = "status", value_parser = TransactionStatus::from_str)]
+    statuses: Vec<TransactionStatus>,
+    /// Repeatable; defaults to issue, redeem and transfer
+    #[arg(long = "type", value_parser = parse_enum::<TransactionType>)]
+    transaction_types: Vec<TransactionType>,
+    #[arg(long)]
+    min_amount: Option<SilaAmount>,
+    #[arg(long)]
+    max_amount: Option<SilaAmount>,
+    #[arg(long)]
+    start_epoch: Option<i64>,
+    #[arg(long)]
+    end_epoch: Option<i64>,
+    #[arg(long)]
+    bank_account_name: Option<String>,
+    #[arg(long)]
+    blockchain_address: Option<String>,
+    #[arg(long, value_parser = parse_enum::<IssueProcessingType>)]
+    processing_type: Option<IssueProcessingType>,
+    #[arg(long)]
+    payment_method_id: Option<String>,
+    #[arg(long)]
+    ascending: bool,
+    #[arg(long)]
+    no_timelines: bool,
+    #[arg(long)]
+    page: Option<i32>,
+    #[arg(long)]
+    per_page: Option<i32>,
+    /// Walk every page instead of returning one
+    #[arg(long, conflicts_with = "page")]
+    all: bool,
+}
+
+#[derive(Args)]
+struct IssueArgs {
+    handle: String,
+    amount: SilaAmount,
+    #[arg(long)]
+    account_name: Option<String>,
+    #[arg(long, value_parser = parse_enum::<IssueProcessingType>)]
+    processing_type: Option<IssueProcessingType>,
+    #[arg(long)]
+    descriptor: Option<String>,
+    #[arg(long)]
+    source_id: Option<String>,
+    #[arg(long)]
+    destination_id: Option<String>,
+}
+
+#[derive(Args)]
+struct RedeemArgs {
+    handle: String,
+    amount: SilaAmount,
+    #[arg(long)]
+    account_name: Option<String>,
+    #[arg(long, value_parser = parse_enum::<RedeemProcessingType>)]
+    processing_type: Option<RedeemProcessingType>,
+    #[arg(long)]
+    descriptor: Option<String>,
+    #[arg(long)]
+    source_id: Option<String>,
+    #[arg(long)]
+    destination_id: Option<String>,
+}
+
+#[derive(Args)]
+struct TransferArgs {
+    handle: String,
+    destination_handle: String,
+    amount: SilaAmount,
+    #[arg(long)]
+    descriptor: Option<String>,
+    #[arg(long)]
+    destination_address: Option<String>,
+    #[arg(long)]
+    destination_wallet: Option<String>,
+    #[arg(long)]
+    source_id: Option<String>,
+    #[arg(long)]
+    destination_id: Option<String>,
+}
+
+// Parses the wire names used by Sila, e.g. "issue" or "SAME_DAY_ACH".
+fn parse_enum<T: DeserializeOwned + Serialize>(s: &str) -> Result<T, String> {
+    let value: T = serde_json::from_value(Value::String(s.to_string())).map_err(|e| e.to_string())?;
+
+    // enums with a catch-all variant accept anything, so insist on a round trip
+    match serde_json::to_value(&value) {
+        Ok(Value::String(x)) if x == s => Ok(value),
+        _ => Err(format!("unknown value {}", s)),
+    }
+}
+
+async fn signed<T: Serialize>(
+    signer: &Signer,
+    message: &T,
+    user_handle: Option<&str>,
+) -> Result<SignedMessageParams, Error> {
+    let message = serde_json::to_string(message)?;
+    let signatures = signer.sign(message.clone(), user_handle).await?;
+
+    Ok(SignedMessageParams {
+        sila_handle: user_handle.map(|x| x.to_string()),
+        message,
+        usersignature: signatures.usersignature,
+        authsignature: signatures.authsignature,
+    })
+}
+
+fn search_filters(args: &TransactionsArgs) -> TransactionSearchFilters {
+    let defaults = TransactionSearchFilters::default();
+
+    TransactionSearchFilters {
+        transaction_id: args.transaction_id.clone(),
+        reference_id: args.reference_id.clone(),
+        show_timelines: Option::from(!args.no_timelines),
+        sort_ascending: Option::from(args.ascending),
+        max_sila_amount: args.max_amount,
+        min_sila_amount: args.min_amount,
+        statuses: match args.statuses.is_empty() {
+            true => defaults.statuses,
+            false => Option::from(args.statuses.clone()),
+        },
+        start_epoch: args.start_epoch,
+        end_epoch: args.end_epoch,
+        page: args.page.or(defaults.page),
+        per_page: args.per_page.or(defaults.per_page),
+        transaction_types: match args.transaction_types.is_empty() {
+            true => defaults.transaction_types,
+            false => Option::from(args.transaction_types.clone()),
+        },
+        bank_account_name: args.bank_account_name.clone(),
+        blockchain_address: args.blockchain_address.clone(),
+        processing_type: args.processing_type.clone(),
+        payment_method_id: args.payment_method_id.clone(),
+    }
+}
+
+async fn transactions(signer: &Signer, args: &TransactionsArgs) -> Result<Vec<Value>, Error> {
+    // get_transactions takes an optional usersignature; only local signers with a user key add one
+    let user_handle = args.handle.as_deref().filter(|_| signer.user_address().is_some());
+    let filters = search_filters(args);
+
+    let pages = paginate::<GetTransactions, _, _>(filters.page.unwrap_or(1), move |page| {
+        let message = GetTransactionsMessage::from(GetTransactionsMessageParams {
+            sila_handle: args.handle.clone(),
+            reference: Option::None,
+            search_filters: Option::from(TransactionSearchFilters {
+                page: Option::from(page),
+                ..filters.clone()
+            }),
+        });
+
+        async move { get_transactions(&signed(signer, &message, user_handle).await?).await }
+    });
+
+    // without --all only the requested page is fetched
+    let pages: Vec<GetTransactionsResponse> = pages
+        .take(if args.all { usize::MAX } else { 1 })
+        .try_collect()
+        .await?;
+
+    let mut transactions = Vec::new();
+    for x in pages.iter().flat_map(|x| x.transactions.iter().flatten()) {
+        transactions.push(serde_json::to_value(x)?);
+    }
+
+    Ok(transactions)
+}
+
+// Prints the response and reports whether Sila considered the call a success.
+fn report<T: Serialize>(format: OutputFormat, response: &T) -> Result<bool, Error> {
+    let value = serde_json::to_value(response)?;
+    output::print(format, &value);
+
+    Ok(value["success"].as_bool().unwrap_or(false) || value["status"] == "SUCCESS")
+}
+
+async fn run(command: Command, signer: &Signer, format: OutputFormat) -> Result<bool, Error> {
+    match command {
+        Command::CheckHandle { handle } => {
+            let message = HeaderMessage::from(CheckHandleMessageParams { sila_handle: handle });
+            report(format, &check_handle(&signed(signer, &message, Option::None).await?).await?)
+        }
+        Command::GetEntity { handle } => {
+            let message = HeaderMessage::from(RequestEntityMessageParams { sila_handle: handle.clone() });
+            report(format, &get_entity(&signed(signer, &message, Option::from(handle.as_str())).await?).await?)
+        }
+        Command::RequestKyc { handle } => {
+            let message = HeaderMessage::from(RequestKycMessageParams { sila_handle: handle.clone() });
+            report(format, &request_kyc(&signed(signer, &message, Option::from(handle.as_str())).await?).await?)
+        }
+        Command::CheckKyc { handle } => {
+            let message = HeaderMessage::from(CheckKycMessageParams { sila_handle: handle.clone() });
+            report(format, &check_kyc(&signed(signer, &message, Option::from(handle.as_str())).await?).await?)
+        }
+        Command::Transactions(args) => {
+            output::print_transactions(format, &transactions(signer, &args).await?);
+            Ok(true)
+        }
+        Command::Issue(args) => {
+            let defaults = IssueSilaMessageParams::default();
+            let message = IssueSilaMessage::from(IssueSilaMessageParams {
+                sila_handle: args.handle.clone(),
+                amount: args.amount,
+                account_name: args.account_name.or(defaults.account_name),
+                card_name: Option::None,
+                descriptor: args.descriptor,
+                business_uuid: Option::None,
+                processing_type: args.processing_type.or(defaults.processing_type),
+                source_id: args.source_id,
+                destination_id: args.destination_id,
+                reference: Option::None,
+            });
+            report(format, &issue_sila(&signed(signer, &message, Option::from(args.handle.as_str())).await?).await?)
+        }
+        Command::Redeem(args) => {
+            let defaults = RedeemSilaMessageParams::default();
+            let message = RedeemSilaMessage::from(RedeemSilaMessageParams {
+                sila_handle: args.handle.clone(),
+                amount: args.amount,
+                account_name: args.account_name.or(defaults.account_name),
+                descriptor: args.descriptor,
+                processing_type: args.processing_type.or(defaults.processing_type),
+                source_id: args.source_id,
+                destination_id: args.destination_id,
+                ..defaults
+            });
+            report(format, &redeem_sila(&signed(signer, &message, Option::from(args.handle.as_str())).await?).await?)
+        }
+        Command::Transfer(args) => {
+            let message = TransferSilaMessage::from(TransferSilaMessageParams {
+                sila_handle: args.handle.clone(),
+                amount: args.amount,
+                descriptor: args.descriptor,
+                destination_handle: args.destination_handle,
+                destination_address: args.destination_address,
+                destination_wallet: args.destination_wallet,
+                destination_id: args.destination_id,
+                source_id: args.source_id,
+                reference: Option::None,
+            });
+            report(format, &transfer_sila(&signed(signer, &message, Option::from(args.handle.as_str())).await?).await?)
+        }
+        Command::Cancel { handle, transaction_id } => {
+            let message = CancelTransactionMessage::from(CancelTransactionMessageParams {
+                sila_handle: handle.clone(),
+                transaction_id,
+                reference: Option::None,
+            });
+            report(format, &cancel_transaction(&signed(signer, &message, Option::from(handle.as_str())).await?).await?)
+        }
+        Command::Balance { address, handle } => {
+            if address.is_none() && handle.is_none() {
+                return Err(Box::from("balance needs an address or --handle"));
+            }
+
+            let message = GetSilaBalanceMessage::from(GetSilaBalanceMessageParams {
+                sila_handle: handle,
+                address,
+            });
+            report(format, &get_sila_balance(&signed(signer, &message, Option::None).await?).await?)
+        }
+    }
+}
+
+fn setup(cli: &Cli) -> Result<Signer, Error> {
+    let config = Config::load(cli.config.as_deref())?;
+    let profile = config.profile(cli.profile.as_deref())?;
+    profile.apply();
+
+    // SILA_PARAMS panics on these, so report them as ordinary errors first
+    for name in ["SILA_APP_HANDLE", "SILA_APP_ADDRESS"] {
+        if std::env::var(name).is_err() {
+            return Err(Box::from(format!("{} is not set and the profile does not provide it", name)));
+        }
+    }
+
+    Signer::new(profile.signer.as_ref(), cli.user_key.as_deref())
+}
+
+fn main() {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    // the environment is settled before the runtime starts any threads
+    let result = setup(&cli).and_then(|signer| {
+        let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+        runtime.block_on(run(cli.command, &signer, cli.output))
+    });
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
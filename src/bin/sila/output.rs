@@ -0,0 +1,133 @@
+use clap::ValueEnum;
+use serde_json::Value;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(x) => x.clone(),
+        Value::Null => String::new(),
+        x => x.to_string(),
+    }
+}
+
+// Flattens nested objects and arrays into (path, value) rows, e.g. addresses.0.city.
+pub fn flatten(prefix: &str, value: &Value, rows: &mut Vec<(String, String)>) {
+    let key = |x: &str| {
+        if prefix.is_empty() {
+            x.to_string()
+        } else {
+            format!("{}.{}", prefix, x)
+        }
+    };
+
+    match value {
+        Value::Object(map) => map.iter().for_each(|(k, v)| flatten(&key(k), v, rows)),
+        Value::Array(values) => values
+            .iter()
+            .enumerate()
+            .for_each(|(i, v)| flatten(&key(&i.to_string()), v, rows)),
+        Value::Null => {}
+        x => rows.push((prefix.to_string(), scalar(x))),
+    }
+}
+
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            rows.iter()
+                .map(|r| r.get(i).map(|x| x.len()).unwrap_or(0))
+                .chain(std::iter::once(h.len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(c, w)| format!("{:<width$}", c, width = w))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![line(headers.to_vec())];
+    lines.extend(rows.iter().map(|r| line(r.iter().map(|x| x.as_str()).collect())));
+    lines.join("\n")
+}
+
+pub fn print(format: OutputFormat, value: &Value) {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value).unwrap_or_default()),
+        OutputFormat::Table => {
+            let mut rows = Vec::new();
+            flatten("", value, &mut rows);
+
+            let rows: Vec<Vec<String>> = rows.into_iter().map(|(k, v)| vec![k, v]).collect();
+            println!("{}", table(&["FIELD", "VALUE"], &rows));
+        }
+    }
+}
+
+pub fn print_transactions(format: OutputFormat, transactions: &[Value]) {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(transactions).unwrap_or_default()),
+        OutputFormat::Table => {
+            let columns = [
+                "transaction_id",
+                "user_handle",
+                "transaction_type",
+                "status",
+                "sila_amount",
+                "processing_type",
+                "created",
+                "reference_id",
+            ];
+
+            let rows: Vec<Vec<String>> = transactions
+                .iter()
+                .map(|t| columns.iter().map(|c| scalar(&t[*c])).collect())
+                .collect();
+
+            let headers: Vec<String> = columns.iter().map(|x| x.to_uppercase()).collect();
+            let headers: Vec<&str> = headers.iter().map(|x| x.as_str()).collect();
+            println!("{}", table(&headers, &rows));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn flattens_nested_values() {
+        let mut rows = Vec::new();
+        flatten(
+            "",
+            &json!({ "status": "SUCCESS", "entity": { "first_name": "Ada" }, "phones": [{ "phone": "555" }], "message": null }),
+            &mut rows,
+        );
+
+        assert!(rows.contains(&("status".to_string(), "SUCCESS".to_string())));
+        assert!(rows.contains(&("entity.first_name".to_string(), "Ada".to_string())));
+        assert!(rows.contains(&("phones.0.phone".to_string(), "555".to_string())));
+        assert!(!rows.iter().any(|(k, _)| k == "message"));
+    }
+
+    #[test]
+    fn pads_table_columns() {
+        let rendered = table(&["A", "B"], &[vec!["long value".to_string(), "x".to_string()]]);
+        assert_eq!(rendered, "A           B\nlong value  x");
+    }
+}
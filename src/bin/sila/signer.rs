@@ -0,0 +1,129 @@
+use std::env;
+use std::str::FromStr;
+
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde::Serialize;
+use sha3::{Digest, Keccak256};
+use web3::types::{H160, H256};
+
+use silamoney::{default_sign, KeyParams, SignDataPair, SignDataParams, Signatures};
+
+use crate::config::SignerConfig;
+
+type Error = Box<dyn std::error::Error + Sync + Send>;
+
+pub fn address_of(private_key: &str) -> Result<H160, Error> {
+    let secret = SecretKey::from_slice(H256::from_str(private_key)?.as_bytes())?;
+    let public = PublicKey::from_secret_key(&Secp256k1::new(), &secret).serialize_uncompressed();
+
+    Ok(H160::from_slice(&Keccak256::digest(&public[1..])[12..]))
+}
+
+fn key_params(private_key: &str) -> Result<KeyParams, Error> {
+    Ok(KeyParams {
+        address: format!("{:#x}", address_of(private_key)?),
+        private_key: Option::from(private_key.to_string()),
+    })
+}
+
+#[derive(Serialize)]
+struct RemoteSignRequest<'a> {
+    message: &'a str,
+    user_handle: Option<&'a str>,
+}
+
+// Local signing holds the keys in this process; remote signing posts each message to a signing
+// service and expects Signatures back, so keys never reach operator machines.
+pub enum Signer {
+    Local {
+        app: KeyParams,
+        user: Option<KeyParams>,
+    },
+    Remote {
+        url: String,
+        client: reqwest::Client,
+    },
+}
+
+impl Signer {
+    pub fn new(config: Option<&SignerConfig>, user_key: Option<&str>) -> Result<Signer, Error> {
+        match config {
+            Some(SignerConfig::Remote { url }) => Ok(Signer::Remote {
+                url: url.clone(),
+                client: reqwest::Client::new(),
+            }),
+            Some(SignerConfig::Local { app_key, app_key_env }) => {
+                let app_key = match (app_key, app_key_env) {
+                    (Some(x), _) => x.clone(),
+                    (None, Some(x)) => env::var(x).map_err(|_| format!("{} is not set", x))?,
+                    (None, None) => env::var("SILA_APP_KEY").map_err(|_| "SILA_APP_KEY is not set")?,
+                };
+
+                Signer::local(&app_key, user_key)
+            }
+            None => {
+                let app_key = env::var("SILA_APP_KEY").map_err(|_| "SILA_APP_KEY is not set")?;
+                Signer::local(&app_key, user_key)
+            }
+        }
+    }
+
+    fn local(app_key: &str, user_key: Option<&str>) -> Result<Signer, Error> {
+        Ok(Signer::Local {
+            app: key_params(app_key)?,
+            user: user_key.map(key_params).transpose()?,
+        })
+    }
+
+    // The user's address, when this signer can sign for the user locally.
+    pub fn user_address(&self) -> Option<H160> {
+        match self {
+            Signer::Local { user: Some(x), .. } => H160::from_str(&x.address).ok(),
+            _ => Option::None,
+        }
+    }
+
+    // user_handle asks for a usersignature as well as the authsignature.
+    pub async fn sign(&self, message: String, user_handle: Option<&str>) -> Result<Signatures, Error> {
+        match self {
+            Signer::Local { app, user } => {
+                let user_params = match (user_handle, user) {
+                    (Some(_), Some(x)) => Option::from(x.clone()),
+                    (Some(x), None) => {
+                        return Err(Box::from(format!(
+                            "signing for {} needs a user key (--user-key or SILA_USER_KEY)",
+                            x
+                        )))
+                    }
+                    (None, _) => Option::None,
+                };
+
+                Ok(default_sign(SignDataPair::from(SignDataParams {
+                    message,
+                    user_params,
+                    app_params: app.clone(),
+                }))
+                .await)
+            }
+            Signer::Remote { url, client } => {
+                let resp = client
+                    .post(url)
+                    .json(&RemoteSignRequest {
+                        message: &message,
+                        user_handle,
+                    })
+                    .send()
+                    .await?
+                    .error_for_status()?;
+
+                let signatures: Signatures = resp.json().await?;
+
+                if user_handle.is_some() && signatures.usersignature.is_none() {
+                    return Err(Box::from("remote signer did not return a usersignature"));
+                }
+
+                Ok(signatures)
+            }
+        }
+    }
+}
@@ -0,0 +1,478 @@
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use hyper::{Body, Request, Response, StatusCode};
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+use crate::mock::{spawn_server, VERSION};
+
+// Cassettes are JSON files of recorded gateway interactions. CassetteServer::record proxies a real
+// gateway and writes every exchange, redacted, to a cassette; CassetteServer::replay serves a
+// cassette back. Point the client at either with SILA_GATEWAY, as with the mock gateway.
+
+pub const REDACTED: &str = "[REDACTED]";
+
+const REDACTED_HEADERS: [&str; 2] = ["authsignature", "usersignature"];
+
+const REDACTED_FIELDS: [&str; 18] = [
+    "identity_value",
+    "ssn",
+    "plaid_token",
+    "account_number",
+    "routing_number",
+    "card_number",
+    "private_key",
+    "public_token",
+    "first_name",
+    "last_name",
+    "entity_name",
+    "full_name",
+    "account_owner_name",
+    "birthdate",
+    "email",
+    "phone",
+    "street_address_1",
+    "street_address_2",
+];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CassetteRequest {
+    pub endpoint: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CassetteResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Interaction {
+    pub request: CassetteRequest,
+    pub response: CassetteResponse,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Cassette, Box<dyn std::error::Error + Sync + Send>> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+
+    pub fn extend(&mut self, other: Cassette) {
+        self.interactions.extend(other.interactions);
+    }
+}
+
+// Replaces sensitive values anywhere in a JSON body with REDACTED. A redacted name that holds an
+// object, like the phone returned by update/phone, is searched instead of replaced.
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_FIELDS.contains(&key.as_str()) && (value.is_string() || value.is_number()) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+// Decides whether a live request matches a recorded one. Paths are dot-separated and name body
+// fields that differ on every run; they are ignored when comparing.
+#[derive(Clone)]
+pub struct CassetteMatcher {
+    pub ignored_paths: Vec<String>,
+}
+
+impl Default for CassetteMatcher {
+    fn default() -> Self {
+        CassetteMatcher {
+            ignored_paths: vec!["header.reference".to_string(), "header.created".to_string()],
+        }
+    }
+}
+
+impl CassetteMatcher {
+    pub fn ignore(mut self, path: &str) -> Self {
+        self.ignored_paths.push(path.to_string());
+        self
+    }
+
+    fn normalize(&self, body: &Value) -> Value {
+        let mut body = body.clone();
+        redact(&mut body);
+
+        for path in &self.ignored_paths {
+            let mut parts: Vec<&str> = path.split('.').collect();
+            let last = parts.pop().unwrap_or_default();
+
+            let parent = parts
+                .into_iter()
+                .try_fold(&mut body, |value, part| value.get_mut(part));
+
+            if let Some(Value::Object(map)) = parent {
+                map.remove(last);
+            }
+        }
+
+        body
+    }
+
+    pub fn matches(&self, recorded: &CassetteRequest, endpoint: &str, body: &Value) -> bool {
+        recorded.endpoint == endpoint && self.normalize(&recorded.body) == self.normalize(body)
+    }
+}
+
+// The path after the version plus any query string, e.g. update/phone or get_entities?page=2, so
+// endpoints that share a last segment and pages of the same listing are told apart.
+fn endpoint_of(req: &Request<Body>) -> String {
+    let path = req.uri().path();
+    let endpoint = path.strip_prefix(&format!("/{}/", VERSION)).unwrap_or(path);
+
+    match req.uri().query() {
+        Some(x) => format!("{}?{}", endpoint, x),
+        None => endpoint.to_string(),
+    }
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+// Non-JSON bodies (multipart document uploads) are kept as null and match on endpoint alone.
+fn parse_body(bytes: &[u8]) -> Value {
+    serde_json::from_slice(bytes).unwrap_or(Value::Null)
+}
+
+struct ReplayState {
+    cassette: Cassette,
+    matcher: CassetteMatcher,
+    played: Vec<bool>,
+}
+
+impl ReplayState {
+    // Each recorded interaction is played once, in order; once every match has been played the
+    // last one repeats, which keeps polling loops deterministic.
+    fn play(&mut self, endpoint: &str, body: &Value) -> Option<CassetteResponse> {
+        let matching: Vec<usize> = self
+            .cassette
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| self.matcher.matches(&x.request, endpoint, body))
+            .map(|(i, _)| i)
+            .collect();
+
+        let index = matching
+            .iter()
+            .copied()
+            .find(|i| !self.played[*i])
+            .or_else(|| matching.last().copied())?;
+
+        self.played[index] = true;
+        let interaction = &self.cassette.interactions[index];
+        let mut response = interaction.response.clone();
+
+        // echo the live reference where the recording echoed the recorded one
+        let recorded_reference = &interaction.request.body["header"]["reference"];
+        if !recorded_reference.is_null() && &response.body["reference"] == recorded_reference {
+            response.body["reference"] = body["header"]["reference"].clone();
+        }
+
+        Option::from(response)
+    }
+}
+
+async fn replay(state: Arc<Mutex<ReplayState>>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let endpoint = endpoint_of(&req);
+
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(x) => parse_body(&x),
+        Err(e) => {
+            return Ok(json_response(
+                StatusCode::BAD_REQUEST,
+                &json!({ "success": false, "status": "FAILURE", "message": e.to_string() }),
+            ))
+        }
+    };
+
+    let response = state.lock().unwrap().play(&endpoint, &body);
+
+    match response {
+        Some(x) => Ok(json_response(
+            StatusCode::from_u16(x.status).unwrap_or(StatusCode::OK),
+            &x.body,
+        )),
+        None => {
+            error!("no cassette interaction matches {} request: {}", endpoint, body);
+            Ok(json_response(
+                StatusCode::NOT_FOUND,
+                &json!({
+                    "success": false,
+                    "status": "FAILURE",
+                    "message": format!("no cassette interaction matches this {} request", endpoint),
+                    "reference": body["header"]["reference"],
+                }),
+            ))
+        }
+    }
+}
+
+struct RecordState {
+    upstream: String,
+    path: PathBuf,
+    cassette: Cassette,
+}
+
+async fn record(state: Arc<Mutex<RecordState>>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let endpoint = endpoint_of(&req);
+    // endpoint keeps the query string, so paging parameters reach the gateway
+    let upstream = format!("{}/{}", state.lock().unwrap().upstream, endpoint);
+
+    let mut headers = BTreeMap::new();
+    for name in ["authsignature", "usersignature", "content-type"] {
+        if let Some(x) = req.headers().get(name).and_then(|x| x.to_str().ok()) {
+            headers.insert(name.to_string(), x.to_string());
+        }
+    }
+
+    let bytes = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(x) => x,
+        Err(e) => {
+            return Ok(json_response(
+                StatusCode::BAD_REQUEST,
+                &json!({ "success": false, "status": "FAILURE", "message": e.to_string() }),
+            ))
+        }
+    };
+
+    let mut request = reqwest::Client::new().post(&upstream).body(bytes.to_vec());
+    for (name, value) in &headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+
+    let (status, text) = match request.send().await {
+        Ok(resp) => {
+            let status = resp.status().as_u16();
+            (status, resp.text().await.unwrap_or_default())
+        }
+        Err(e) => {
+            error!("cassette recorder could not reach {}: {}", upstream, e);
+            return Ok(json_response(
+                StatusCode::BAD_GATEWAY,
+                &json!({ "success": false, "status": "FAILURE", "message": e.to_string() }),
+            ));
+        }
+    };
+
+    let mut recorded_headers = headers.clone();
+    for name in REDACTED_HEADERS {
+        if let Some(x) = recorded_headers.get_mut(name) {
+            *x = REDACTED.to_string();
+        }
+    }
+
+    let mut request_body = parse_body(&bytes);
+    redact(&mut request_body);
+
+    let mut response_body = serde_json::from_str(&text).unwrap_or(Value::String(text.clone()));
+    redact(&mut response_body);
+
+    {
+        let mut state = state.lock().unwrap();
+        state.cassette.interactions.push(Interaction {
+            request: CassetteRequest {
+                endpoint,
+                headers: recorded_headers,
+                body: request_body,
+            },
+            response: CassetteResponse {
+                status,
+                body: response_body,
+            },
+        });
+
+        if let Err(e) = state.cassette.save(&state.path) {
+            error!("could not write cassette {}: {}", state.path.display(), e);
+        }
+    }
+
+    Ok(Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(text))
+        .unwrap_or_else(|_| Response::new(Body::empty())))
+}
+
+pub struct CassetteServer {
+    address: SocketAddr,
+    replay: Option<Arc<Mutex<ReplayState>>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl CassetteServer {
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<CassetteServer, Box<dyn std::error::Error + Sync + Send>> {
+        CassetteServer::replay_with(Cassette::load(path)?, CassetteMatcher::default())
+    }
+
+    pub fn replay_with(
+        cassette: Cassette,
+        matcher: CassetteMatcher,
+    ) -> Result<CassetteServer, Box<dyn std::error::Error + Sync + Send>> {
+        let state = Arc::new(Mutex::new(ReplayState {
+            played: vec![false; cassette.interactions.len()],
+            cassette,
+            matcher,
+        }));
+
+        let server_state = state.clone();
+        let (address, shutdown) = spawn_server("cassette replay", move |req| replay(server_state.clone(), req))?;
+
+        Ok(CassetteServer {
+            address,
+            replay: Option::from(state),
+            shutdown: Option::from(shutdown),
+        })
+    }
+
+    // Proxies to upstream (e.g. the sandbox gateway) and rewrites the cassette at path after every
+    // interaction.
+    pub fn record<P: AsRef<Path>>(
+        upstream: &str,
+        path: P,
+    ) -> Result<CassetteServer, Box<dyn std::error::Error + Sync + Send>> {
+        let state = Arc::new(Mutex::new(RecordState {
+            upstream: upstream.trim_end_matches('/').to_string(),
+            path: path.as_ref().to_path_buf(),
+            cassette: Cassette::default(),
+        }));
+
+        let (address, shutdown) = spawn_server("cassette recorder", move |req| record(state.clone(), req))?;
+
+        Ok(CassetteServer {
+            address,
+            replay: Option::None,
+            shutdown: Option::from(shutdown),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/{}", self.address, VERSION)
+    }
+
+    // Endpoints of recorded interactions that have not been replayed yet.
+    pub fn unplayed(&self) -> Vec<String> {
+        match &self.replay {
+            Some(state) => {
+                let state = state.lock().unwrap();
+                state
+                    .cassette
+                    .interactions
+                    .iter()
+                    .zip(state.played.iter())
+                    .filter(|(_, played)| !**played)
+                    .map(|(x, _)| x.request.endpoint.clone())
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Drop for CassetteServer {
+    fn drop(&mut self) {
+        if let Some(x) = self.shutdown.take() {
+            x.send(()).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matcher_ignores_reference_and_created() {
+        let recorded = CassetteRequest {
+            endpoint: "get_entity".to_string(),
+            headers: BTreeMap::new(),
+            body: json!({ "header": { "reference": "a", "created": 1, "user_handle": "user" } }),
+        };
+
+        let matcher = CassetteMatcher::default();
+        let live = json!({ "header": { "reference": "b", "created": 2, "user_handle": "user" } });
+        assert!(matcher.matches(&recorded, "get_entity", &live));
+        assert!(!matcher.matches(&recorded, "check_kyc", &live));
+
+        let other = json!({ "header": { "reference": "b", "created": 2, "user_handle": "other" } });
+        assert!(!matcher.matches(&recorded, "get_entity", &other));
+    }
+
+    #[test]
+    fn redacts_nested_fields() {
+        let mut body = json!({
+            "identity": { "identity_alias": "SSN", "identity_value": "123456222" },
+            "accounts": [{ "account_number": "1234", "account_name": "default" }],
+        });
+        redact(&mut body);
+
+        assert_eq!(body["identity"]["identity_value"], json!(REDACTED));
+        assert_eq!(body["identity"]["identity_alias"], json!("SSN"));
+        assert_eq!(body["accounts"][0]["account_number"], json!(REDACTED));
+        assert_eq!(body["accounts"][0]["account_name"], json!("default"));
+    }
+
+    #[test]
+    fn redacts_personal_details_inside_objects_of_the_same_name() {
+        let mut body = json!({
+            "entity": { "first_name": "Jane", "last_name": "Doe", "birthdate": "1990-01-01" },
+            "phone": { "uuid": "a", "phone": "+15035550100" },
+            "email": "jane@example.com",
+            "addresses": [{ "street_address_1": "1 Main St", "city": "Portland" }],
+        });
+        redact(&mut body);
+
+        assert_eq!(body["entity"]["first_name"], json!(REDACTED));
+        assert_eq!(body["entity"]["birthdate"], json!(REDACTED));
+        assert_eq!(body["phone"]["uuid"], json!("a"));
+        assert_eq!(body["phone"]["phone"], json!(REDACTED));
+        assert_eq!(body["email"], json!(REDACTED));
+        assert_eq!(body["addresses"][0]["street_address_1"], json!(REDACTED));
+        assert_eq!(body["addresses"][0]["city"], json!("Portland"));
+    }
+
+    #[test]
+    fn endpoints_keep_their_full_path_and_query() {
+        let endpoint = |uri: &str| endpoint_of(&Request::builder().uri(uri).body(Body::empty()).unwrap());
+
+        assert_eq!(endpoint("/0.2/update/phone"), "update/phone");
+        assert_eq!(endpoint("/0.2/add/phone"), "add/phone");
+        assert_eq!(endpoint("/0.2/get_entities?page=2&per_page=1"), "get_entities?page=2&per_page=1");
+    }
+}
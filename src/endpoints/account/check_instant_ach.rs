@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Status,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct CheckInstantAchMessage {
+    pub header: Header,
+    pub account_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kyc_level: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct CheckInstantAchMessageParams {
+    pub sila_handle: String,
+    pub account_name: Option<String>,
+    pub kyc_level: Option<String>,
+}
+
+impl From<CheckInstantAchMessageParams> for CheckInstantAchMessage {
+    fn from(params: CheckInstantAchMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        CheckInstantAchMessage {
+            header: header_message.header,
+            account_name: params.account_name.unwrap_or_else(|| "default".to_string()),
+            kyc_level: params.kyc_level,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CheckInstantAchResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub validation_details: Option<serde_json::Value>,
+}
+
+pub struct InstantAchEligibility {
+    pub eligible: bool,
+    pub reasons: Vec<String>,
+}
+
+impl std::fmt::Display for InstantAchEligibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "InstantAchEligibility(eligible: {}, reasons: [{}])",
+            self.eligible,
+            self.reasons.join("; ")
+        )
+    }
+}
+
+fn collect_reasons(value: &serde_json::Value, reasons: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(x) => reasons.push(x.clone()),
+        serde_json::Value::Array(x) => x.iter().for_each(|v| collect_reasons(v, reasons)),
+        serde_json::Value::Object(x) => x.values().for_each(|v| collect_reasons(v, reasons)),
+        _ => {}
+    }
+}
+
+impl From<&CheckInstantAchResponse> for InstantAchEligibility {
+    fn from(response: &CheckInstantAchResponse) -> Self {
+        let eligible = response.success && response.status == Status::SUCCESS;
+        let mut reasons = Vec::new();
+
+        if !eligible {
+            if let Some(x) = &response.message {
+                reasons.push(x.clone());
+            }
+            if let Some(x) = &response.validation_details {
+                collect_reasons(x, &mut reasons);
+            }
+        }
+
+        InstantAchEligibility { eligible, reasons }
+    }
+}
+
+pub struct CheckInstantAch;
+
+impl SilaEndpoint for CheckInstantAch {
+    type Message = CheckInstantAchMessage;
+    type Response = CheckInstantAchResponse;
+
+    const PATH: &'static str = "check_instant_ach";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &CheckInstantAchResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn check_instant_ach(
+    params: &SignedMessageParams,
+) -> Result<CheckInstantAchResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<CheckInstantAch>(params).await
+}
@@ -0,0 +1,304 @@
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::virtual_account::VirtualAccount;
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Status,
+};
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMethodType {
+    BankAccount,
+    BlockchainAddress,
+    VirtualAccount,
+    Card,
+}
+
+impl std::fmt::Display for PaymentMethodType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            PaymentMethodType::BankAccount => write!(f, "bank_account"),
+            PaymentMethodType::BlockchainAddress => write!(f, "blockchain_address"),
+            PaymentMethodType::VirtualAccount => write!(f, "virtual_account"),
+            PaymentMethodType::Card => write!(f, "card"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct PaymentMethodSearchFilters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_method_types: Option<Vec<PaymentMethodType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blockchain_network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_virtual_account_balance: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct GetPaymentMethodsMessage {
+    pub header: Header,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_filters: Option<PaymentMethodSearchFilters>,
+}
+
+#[derive(Clone, Default)]
+pub struct GetPaymentMethodsMessageParams {
+    pub sila_handle: String,
+    pub search_filters: Option<PaymentMethodSearchFilters>,
+}
+
+impl From<GetPaymentMethodsMessageParams> for GetPaymentMethodsMessage {
+    fn from(params: GetPaymentMethodsMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        GetPaymentMethodsMessage {
+            header: header_message.header,
+            search_filters: params.search_filters.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct BankAccountPaymentMethod {
+    pub bank_account_id: Option<String>,
+    pub account_name: Option<String>,
+    pub account_number: Option<String>,
+    pub routing_number: Option<String>,
+    pub account_type: Option<String>,
+    pub account_status: Option<String>,
+    pub account_owner_name: Option<String>,
+    pub account_link_status: Option<String>,
+    pub active: Option<bool>,
+    pub match_score: Option<f32>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct BlockchainAddressPaymentMethod {
+    pub blockchain_address_id: Option<String>,
+    pub blockchain_address: Option<String>,
+    pub blockchain_network: Option<String>,
+    pub nickname: Option<String>,
+    pub default: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct CardPaymentMethod {
+    pub card_id: Option<String>,
+    pub card_name: Option<String>,
+    pub last_4: Option<String>,
+    pub expiration: Option<String>,
+    pub card_network: Option<String>,
+    pub card_type: Option<String>,
+    pub active: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(tag = "payment_method_type", rename_all = "snake_case")]
+pub enum PaymentMethod {
+    BankAccount(BankAccountPaymentMethod),
+    BlockchainAddress(BlockchainAddressPaymentMethod),
+    VirtualAccount(VirtualAccount),
+    Card(CardPaymentMethod),
+    #[serde(other)]
+    Unknown,
+}
+
+impl PaymentMethod {
+    pub fn payment_method_type(&self) -> Option<PaymentMethodType> {
+        match self {
+            PaymentMethod::BankAccount(_) => Option::from(PaymentMethodType::BankAccount),
+            PaymentMethod::BlockchainAddress(_) => Option::from(PaymentMethodType::BlockchainAddress),
+            PaymentMethod::VirtualAccount(_) => Option::from(PaymentMethodType::VirtualAccount),
+            PaymentMethod::Card(_) => Option::from(PaymentMethodType::Card),
+            PaymentMethod::Unknown => Option::None,
+        }
+    }
+
+    // the identifier accepted as source_id / destination_id by issue, redeem and transfer
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            PaymentMethod::BankAccount(x) => x.bank_account_id.as_deref(),
+            PaymentMethod::BlockchainAddress(x) => x.blockchain_address_id.as_deref(),
+            PaymentMethod::VirtualAccount(x) => x.virtual_account_id.as_deref(),
+            PaymentMethod::Card(x) => x.card_id.as_deref(),
+            PaymentMethod::Unknown => Option::None,
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            PaymentMethod::BankAccount(x) => x.account_name.as_deref(),
+            PaymentMethod::BlockchainAddress(x) => x.nickname.as_deref(),
+            PaymentMethod::VirtualAccount(x) => x.virtual_account_name.as_deref(),
+            PaymentMethod::Card(x) => x.card_name.as_deref(),
+            PaymentMethod::Unknown => Option::None,
+        }
+    }
+}
+
+impl std::fmt::Display for PaymentMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PaymentMethod(type: {}, id: {}, name: {})",
+            self.payment_method_type().map(|x| x.to_string()).unwrap_or_else(|| "unknown".to_string()),
+            self.id().unwrap_or("none"),
+            self.name().unwrap_or("none")
+        )
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct GetPaymentMethodsResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub payment_methods: Option<Vec<PaymentMethod>>,
+}
+
+impl GetPaymentMethodsResponse {
+    pub fn of_type(&self, payment_method_type: PaymentMethodType) -> Vec<&PaymentMethod> {
+        self.payment_methods
+            .iter()
+            .flatten()
+            .filter(|x| x.payment_method_type().as_ref() == Some(&payment_method_type))
+            .collect()
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&PaymentMethod> {
+        self.payment_methods
+            .iter()
+            .flatten()
+            .find(|x| x.name() == Some(name))
+    }
+}
+
+pub struct GetPaymentMethods;
+
+impl SilaEndpoint for GetPaymentMethods {
+    type Message = GetPaymentMethodsMessage;
+    type Response = GetPaymentMethodsResponse;
+
+    const PATH: &'static str = "get_payment_methods";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &GetPaymentMethodsResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn get_payment_methods(
+    params: &SignedMessageParams,
+) -> Result<GetPaymentMethodsResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<GetPaymentMethods>(params).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(body: serde_json::Value) -> GetPaymentMethodsResponse {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn decodes_each_payment_method_type() {
+        let response = decode(serde_json::json!({
+            "success": true,
+            "status": "SUCCESS",
+            "payment_methods": [
+                {
+                    "payment_method_type": "bank_account",
+                    "bank_account_id": "ba-1",
+                    "account_name": "default",
+                    "account_number": "*1234",
+                    "routing_number": "123456780",
+                    "account_type": "CHECKING",
+                    "account_status": "active",
+                    "account_owner_name": "Ada Lovelace",
+                    "account_link_status": "processorTokenOnly",
+                    "active": true,
+                    "match_score": 0.95
+                },
+                {
+                    "payment_method_type": "blockchain_address",
+                    "blockchain_address_id": "bc-1",
+                    "blockchain_address": "0x65a796a4bD3AaF6370791BefFb1A86EAcfdBc3C1",
+                    "blockchain_network": "ETH",
+                    "nickname": "wallet",
+                    "default": true
+                },
+                {
+                    "payment_method_type": "virtual_account",
+                    "virtual_account_id": "va-1",
+                    "virtual_account_name": "savings",
+                    "account_number": "1000000001",
+                    "routing_number": "084106768",
+                    "active": true,
+                    "closed": false
+                },
+                {
+                    "payment_method_type": "card",
+                    "card_id": "card-1",
+                    "card_name": "visa",
+                    "last_4": "4242",
+                    "expiration": "2030-12",
+                    "card_network": "VISA",
+                    "card_type": "DEBIT",
+                    "active": true
+                }
+            ]
+        }));
+
+        let methods = response.payment_methods.as_ref().unwrap();
+        assert!(matches!(&methods[0], PaymentMethod::BankAccount(x) if x.match_score == Some(0.95)));
+        assert!(matches!(&methods[1], PaymentMethod::BlockchainAddress(x) if x.default == Some(true)));
+        assert!(matches!(&methods[2], PaymentMethod::VirtualAccount(x) if x.closed == Some(false)));
+        assert!(matches!(&methods[3], PaymentMethod::Card(x) if x.last_4.as_deref() == Some("4242")));
+
+        let ids: Vec<Option<&str>> = methods.iter().map(|x| x.id()).collect();
+        assert_eq!(ids, vec![Some("ba-1"), Some("bc-1"), Some("va-1"), Some("card-1")]);
+
+        let names: Vec<Option<&str>> = methods.iter().map(|x| x.name()).collect();
+        assert_eq!(names, vec![Some("default"), Some("wallet"), Some("savings"), Some("visa")]);
+
+        assert_eq!(response.of_type(PaymentMethodType::Card).len(), 1);
+        assert!(response.find_by_name("savings").unwrap().payment_method_type() == Some(PaymentMethodType::VirtualAccount));
+    }
+
+    #[test]
+    fn decodes_unknown_types_and_partial_methods() {
+        let response = decode(serde_json::json!({
+            "success": true,
+            "status": "SUCCESS",
+            "response_time_ms": "12",
+            "payment_methods": [
+                {"payment_method_type": "crypto_card", "crypto_card_id": "cc-1", "network": "SOL"},
+                {"payment_method_type": "card", "card_id": "card-2"},
+                {"payment_method_type": "bank_account", "account_name": "default", "plaid_item_id": "item-1"}
+            ]
+        }));
+
+        let methods = response.payment_methods.as_ref().unwrap();
+        assert!(matches!(methods[0], PaymentMethod::Unknown));
+        assert!(methods[0].payment_method_type().is_none());
+        assert_eq!(methods[0].id(), Option::None);
+        assert_eq!(methods[0].to_string(), "PaymentMethod(type: unknown, id: none, name: none)");
+
+        assert!(matches!(&methods[1], PaymentMethod::Card(x) if x.card_name.is_none() && x.active.is_none()));
+        assert_eq!(methods[2].id(), Option::None);
+        assert_eq!(methods[2].name(), Some("default"));
+
+        assert!(response.of_type(PaymentMethodType::BlockchainAddress).is_empty());
+
+        let empty = decode(serde_json::json!({"success": false, "status": "FAILURE", "message": "no methods"}));
+        assert!(empty.payment_methods.is_none());
+        assert!(empty.find_by_name("default").is_none());
+    }
+}
@@ -1,7 +1,9 @@
-use log::error;
 use serde::{Deserialize, Serialize};
 
-use crate::{header_message, Header, HeaderMessage, SignedMessageParams, Status};
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Status,
+};
 
 #[derive(Clone)]
 pub struct LinkMessageParams {
@@ -38,10 +40,9 @@ impl From<LinkMessageParams> for LinkMessage {
         header.header.user_handle = Option::from(params.sila_handle.clone());
         header.header.auth_handle = sila_params.app_handle.clone();
 
-        let mut account_name = "default".to_string();
-        if params.account_name.is_some() {
-            account_name = params.account_name.unwrap();
-        }
+        let account_name = params
+            .account_name
+            .unwrap_or_else(|| "default".to_string());
         
         LinkMessage {
             header: header.header,
@@ -63,35 +64,22 @@ pub struct LinkResponse {
     pub web_debit_verified: Option<bool>,
 }
 
-pub async fn link_account(
-    params: &SignedMessageParams,
-) -> Result<LinkResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
-    let _url: String = format!("{}/link_account", sila_params.gateway);
-
-    let h: LinkMessage = serde_json::from_str(&params.message.clone()).unwrap();
+pub struct LinkAccount;
 
-    let client = reqwest::Client::new();
-    let resp = client
-        .post(&_url.to_owned())
-        .header("usersignature", params.usersignature.clone().unwrap())
-        .header("authsignature", params.authsignature.clone())
-        .json(&h)
-        .send()
-        .await?;
+impl SilaEndpoint for LinkAccount {
+    type Message = LinkMessage;
+    type Response = LinkResponse;
 
-    let response_text = resp.text().await?;
-    let response: Result<LinkResponse, serde_json::Error> = serde_json::from_str(&response_text);
+    const PATH: &'static str = "link_account";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
 
-    match response {
-        Ok(x) if x.status == Status::FAILURE => {
-            error!("link_account error: {}", response_text);
-            Ok(x)
-        }
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("link_account response error: {}", response_text);
-            Err(Box::from(e))
-        }
+    fn is_failure(response: &LinkResponse) -> bool {
+        response.status == Status::FAILURE
     }
 }
+
+pub async fn link_account(
+    params: &SignedMessageParams,
+) -> Result<LinkResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<LinkAccount>(params).await
+}
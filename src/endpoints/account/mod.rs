@@ -1 +1,3 @@
+pub mod check_instant_ach;
+pub mod get_payment_methods;
 pub mod link_account;
\ No newline at end of file
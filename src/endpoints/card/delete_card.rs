@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Status,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct DeleteCardMessage {
+    pub header: Header,
+    pub card_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct DeleteCardMessageParams {
+    pub sila_handle: String,
+    pub card_name: String,
+    pub provider: Option<String>,
+}
+
+impl From<DeleteCardMessageParams> for DeleteCardMessage {
+    fn from(params: DeleteCardMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        DeleteCardMessage {
+            header: header_message.header,
+            card_name: params.card_name.clone(),
+            provider: params.provider.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DeleteCardResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub card_name: Option<String>,
+}
+
+pub struct DeleteCard;
+
+impl SilaEndpoint for DeleteCard {
+    type Message = DeleteCardMessage;
+    type Response = DeleteCardResponse;
+
+    const PATH: &'static str = "delete_card";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &DeleteCardResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn delete_card(
+    params: &SignedMessageParams,
+) -> Result<DeleteCardResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<DeleteCard>(params).await
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::card::*;
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Status,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct GetCardsMessage {
+    pub header: Header,
+}
+
+#[derive(Clone)]
+pub struct GetCardsMessageParams {
+    pub sila_handle: String,
+}
+
+impl From<GetCardsMessageParams> for GetCardsMessage {
+    fn from(params: GetCardsMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        GetCardsMessage {
+            header: header_message.header,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct GetCardsResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub cards: Option<Vec<Card>>,
+}
+
+pub struct GetCards;
+
+impl SilaEndpoint for GetCards {
+    type Message = GetCardsMessage;
+    type Response = GetCardsResponse;
+
+    const PATH: &'static str = "get_cards";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &GetCardsResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn get_cards(
+    params: &SignedMessageParams,
+) -> Result<GetCardsResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<GetCards>(params).await
+}
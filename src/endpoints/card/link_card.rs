@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Status,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct LinkCardMessage {
+    pub header: Header,
+    pub card_name: String,
+    pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_postal_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+}
+
+// token is the tokenized card produced by the card processor's hosted form; raw card numbers
+// never pass through this crate
+#[derive(Clone)]
+pub struct LinkCardMessageParams {
+    pub sila_handle: String,
+    pub card_name: Option<String>,
+    pub token: String,
+    pub account_postal_code: Option<String>,
+    pub provider: Option<String>,
+}
+
+impl std::fmt::Display for LinkCardMessageParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LinkCardParams ( sila_handle: {}, card_name: {} )",
+            self.sila_handle,
+            self.card_name.as_ref().unwrap_or(&"default".to_string()))
+    }
+}
+
+impl From<LinkCardMessageParams> for LinkCardMessage {
+    fn from(params: LinkCardMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        LinkCardMessage {
+            header: header_message.header,
+            card_name: params.card_name.unwrap_or_else(|| "default".to_string()),
+            token: params.token.clone(),
+            account_postal_code: params.account_postal_code.clone(),
+            provider: params.provider.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct LinkCardResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub card_name: Option<String>,
+    pub avs: Option<String>,
+}
+
+pub struct LinkCard;
+
+impl SilaEndpoint for LinkCard {
+    type Message = LinkCardMessage;
+    type Response = LinkCardResponse;
+
+    const PATH: &'static str = "link_card";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &LinkCardResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn link_card(
+    params: &SignedMessageParams,
+) -> Result<LinkCardResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<LinkCard>(params).await
+}
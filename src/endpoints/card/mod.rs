@@ -0,0 +1,29 @@
+pub mod delete_card;
+pub mod get_cards;
+pub mod link_card;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Card {
+    pub card_id: Option<String>,
+    pub card_name: Option<String>,
+    pub last_4: Option<String>,
+    pub expiration: Option<String>,
+    pub card_network: Option<String>,
+    pub card_type: Option<String>,
+    pub card_status: Option<String>,
+    pub active: Option<bool>,
+    pub provider: Option<String>,
+}
+
+impl std::fmt::Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Card(card_name: {}, card_network: {}, last_4: {}, expiration: {})",
+            self.card_name.as_ref().unwrap_or(&"none".to_string()),
+            self.card_network.as_ref().unwrap_or(&"none".to_string()),
+            self.last_4.as_ref().unwrap_or(&"none".to_string()),
+            self.expiration.as_ref().unwrap_or(&"none".to_string())
+        )
+    }
+}
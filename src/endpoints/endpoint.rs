@@ -0,0 +1,220 @@
+use std::time::Instant;
+
+use log::error;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::{Instrument, Span};
+
+use crate::{
+    middleware, multipart_form, stats, telemetry, MultipartFile, SignedMessageParams, SilaReply,
+    SilaRequest,
+};
+
+// Which signature headers an endpoint sends with its message.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RequiredSignatures {
+    // authsignature only; a usersignature in the params is not sent
+    App,
+    // authsignature and usersignature; a request without a usersignature is refused
+    User,
+    // authsignature, plus the usersignature when the params carry one
+    OptionalUser,
+}
+
+// An endpoint is a type declaration: the message posted to PATH, the response decoded from the
+// reply and the signatures it needs. execute does the rest, so anything that applies to every
+// call belongs there rather than in the endpoint modules.
+pub trait SilaEndpoint {
+    type Message: Serialize + DeserializeOwned + Send + Sync + 'static;
+    type Response: DeserializeOwned;
+
+    const PATH: &'static str;
+    const SIGNATURES: RequiredSignatures;
+
+    // Checks run on the decoded message before anything is sent.
+    fn check(_message: &Self::Message) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        Ok(())
+    }
+
+    // A decoded response that Sila marked as failed; it is logged and still returned.
+    fn is_failure(response: &Self::Response) -> bool;
+}
+
+pub async fn execute<E: SilaEndpoint>(
+    params: &SignedMessageParams,
+) -> Result<E::Response, Box<dyn std::error::Error + Sync + Send>> {
+    execute_with_query::<E>(params, &[]).await
+}
+
+// For the few endpoints that take paging in the query string rather than the message.
+pub async fn execute_with_query<E: SilaEndpoint>(
+    params: &SignedMessageParams,
+    query: &[(&str, String)],
+) -> Result<E::Response, Box<dyn std::error::Error + Sync + Send>> {
+    run::<E>(params, query, Payload::Json).await
+}
+
+// How the signed message goes on the wire: as the JSON body, or as the "data" part of a
+// multipart form carrying files whose hashes it signs.
+pub(crate) enum Payload {
+    Json,
+    Multipart(Vec<MultipartFile>),
+}
+
+// Every client request is built from this, so the configured timeout applies to all of them.
+pub(crate) fn client() -> Result<reqwest::Client, Box<dyn std::error::Error + Sync + Send>> {
+    Ok(reqwest::Client::builder()
+        .timeout(crate::SILA_PARAMS.timeout)
+        .build()?)
+}
+
+pub(crate) async fn run<E: SilaEndpoint>(
+    params: &SignedMessageParams,
+    query: &[(&str, String)],
+    payload: Payload,
+) -> Result<E::Response, Box<dyn std::error::Error + Sync + Send>> {
+    let message: serde_json::Value = serde_json::from_str(&params.message).unwrap_or_default();
+    let span = telemetry::request_span(E::PATH, &message);
+    let started = Instant::now();
+    stats::record_request(E::PATH);
+
+    let result = send::<E>(params, query, payload, &span).instrument(span.clone()).await;
+    let latency = started.elapsed();
+
+    match &result {
+        Ok(x) => {
+            let failed = E::is_failure(x);
+            telemetry::record_outcome(&span, latency, failed, Option::None);
+            stats::record_outcome(E::PATH, latency, failed);
+        }
+        Err(e) => {
+            let error = e.to_string();
+            telemetry::record_outcome(&span, latency, true, Option::from(error.as_str()));
+            stats::record_outcome(E::PATH, latency, false);
+        }
+    }
+
+    result
+}
+
+async fn send<E: SilaEndpoint>(
+    params: &SignedMessageParams,
+    query: &[(&str, String)],
+    payload: Payload,
+    span: &Span,
+) -> Result<E::Response, Box<dyn std::error::Error + Sync + Send>> {
+    let sila_params = &*crate::SILA_PARAMS;
+    let _url: String = format!("{}/{}", sila_params.gateway, E::PATH);
+
+    let h: E::Message = serde_json::from_str(&params.message)?;
+
+    if let Err(e) = E::check(&h) {
+        error!("{} refused: {}", E::PATH, e);
+        return Err(e);
+    }
+
+    // The signed message is sent as it was signed; re-serializing h could reorder keys or
+    // reformat numbers and break the signatures. A multipart form is refused here if a file is
+    // not bound to the signed message.
+    let body = params.message.as_str();
+    let form = match payload {
+        Payload::Json => Option::None,
+        Payload::Multipart(files) => Option::from(multipart_form(params, files)?),
+    };
+
+    let mut headers = HeaderMap::new();
+    if form.is_none() {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    }
+    headers.insert("authsignature", HeaderValue::from_str(&params.authsignature)?);
+
+    match (E::SIGNATURES, &params.usersignature) {
+        (RequiredSignatures::App, _) => {}
+        (_, Some(x)) => {
+            headers.insert("usersignature", HeaderValue::from_str(x)?);
+        }
+        (RequiredSignatures::User, None) => {
+            error!("{} refused: no usersignature", E::PATH);
+            return Err(Box::from(format!("{} requires a usersignature", E::PATH)));
+        }
+        (RequiredSignatures::OptionalUser, None) => {}
+    }
+
+    let mut request = SilaRequest {
+        path: E::PATH,
+        message: &h,
+        body,
+        headers,
+    };
+
+    let chain = middleware::chain();
+    let mut reply = Option::None;
+    let mut reached = 0;
+
+    for x in &chain {
+        reached += 1;
+        match x.on_request(&mut request) {
+            Ok(Some(r)) => {
+                reply = Option::from(r);
+                break;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!("{} refused by middleware: {}", E::PATH, e);
+                return Err(e);
+            }
+        }
+    }
+
+    let mut reply = match reply {
+        Some(x) => x,
+        None => {
+            let builder = client()?
+                .post(&_url)
+                .query(query)
+                .headers(request.headers.clone());
+
+            let builder = match form {
+                Some(x) => builder.multipart(x),
+                None => builder.body(body.to_string()),
+            };
+
+            let resp = builder
+                .send()
+                .await
+                .inspect_err(|_| stats::record_transport_error(E::PATH))?;
+
+            SilaReply {
+                status: resp.status().as_u16(),
+                body: resp
+                    .text()
+                    .await
+                    .inspect_err(|_| stats::record_transport_error(E::PATH))?,
+            }
+        }
+    };
+
+    for x in chain[..reached].iter().rev() {
+        x.on_response(&request, &mut reply)?;
+    }
+
+    telemetry::record_reply(span, reply.status, &reply.body);
+    stats::record_reply(E::PATH, reply.status, &reply.body);
+
+    let response_text = reply.body;
+    let response: Result<E::Response, serde_json::Error> = serde_json::from_str(&response_text);
+
+    match response {
+        Ok(x) if E::is_failure(&x) => {
+            error!("{} failure: {}", E::PATH, response_text);
+            Ok(x)
+        }
+        Ok(x) => Ok(x),
+        Err(e) => {
+            error!("{} response error: {}", E::PATH, response_text);
+            stats::record_decode_error(E::PATH);
+            Err(Box::from(e))
+        }
+    }
+}
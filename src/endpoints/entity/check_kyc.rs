@@ -1,4 +1,5 @@
 use crate::endpoints::entity::*;
+use crate::{execute, RequiredSignatures, SilaEndpoint};
 
 #[derive(Clone)]
 pub struct CheckKycMessageParams {
@@ -17,35 +18,22 @@ impl From<CheckKycMessageParams> for HeaderMessage {
     }
 }
 
+pub struct CheckKyc;
+
+impl SilaEndpoint for CheckKyc {
+    type Message = HeaderMessage;
+    type Response = CheckResponse;
+
+    const PATH: &'static str = "check_kyc";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &CheckResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
 pub async fn check_kyc(
     params: &SignedMessageParams,
 ) -> Result<CheckResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
-    let _url: String = format!("{}/check_kyc", sila_params.gateway);
-
-    let h: HeaderMessage = serde_json::from_str(&params.message.clone()).unwrap();
-
-    let client = reqwest::Client::new();
-    let resp: reqwest::Response = client
-        .post(&_url.to_owned())
-        .header("usersignature", &params.usersignature.clone().unwrap())
-        .header("authsignature", &params.authsignature)
-        .json(&h)
-        .send()
-        .await?;
-
-    let response_text = resp.text().await?;
-    let response: Result<CheckResponse, serde_json::Error> = serde_json::from_str(&response_text);
-
-    match response {
-        Ok(x) if x.status == Status::FAILURE => {
-            error!("general check_kyc error | text: {}", response_text);
-            Ok(x)
-        }
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("decoding error | text: {}", response_text);
-            Err(Box::from(e))
-        }
-    }
+    execute::<CheckKyc>(params).await
 }
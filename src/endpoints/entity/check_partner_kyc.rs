@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::entity::*;
+use crate::{execute, Header, RequiredSignatures, SilaEndpoint};
+
+#[derive(Deserialize, Serialize)]
+pub struct CheckPartnerKycMessage {
+    pub header: Header,
+    pub query_app_handle: String,
+    pub query_user_handle: String,
+}
+
+#[derive(Clone)]
+pub struct CheckPartnerKycMessageParams {
+    pub query_app_handle: String,
+    pub query_user_handle: String,
+}
+
+impl From<CheckPartnerKycMessageParams> for CheckPartnerKycMessage {
+    fn from(params: CheckPartnerKycMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        CheckPartnerKycMessage {
+            header: header_message.header,
+            query_app_handle: params.query_app_handle.clone(),
+            query_user_handle: params.query_user_handle.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CheckPartnerKycResponse {
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub status: Status,
+    pub success: bool,
+    pub entity_type: Option<EntityType>,
+    pub verification_status: Option<String>,
+    pub kyc_level: Option<String>,
+}
+
+impl std::fmt::Display for CheckPartnerKycResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CheckPartnerKycResponse(status: {}, verification_status: {}, kyc_level: {})",
+            self.status,
+            self.verification_status.as_ref().unwrap_or(&"none".to_string()),
+            self.kyc_level.as_ref().unwrap_or(&"none".to_string())
+        )
+    }
+}
+
+pub struct CheckPartnerKyc;
+
+impl SilaEndpoint for CheckPartnerKyc {
+    type Message = CheckPartnerKycMessage;
+    type Response = CheckPartnerKycResponse;
+
+    const PATH: &'static str = "check_partner_kyc";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::App;
+
+    fn is_failure(response: &CheckPartnerKycResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn check_partner_kyc(
+    params: &SignedMessageParams,
+) -> Result<CheckPartnerKycResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<CheckPartnerKyc>(params).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_a_partner_kyc_response() {
+        let response: CheckPartnerKycResponse = serde_json::from_str(
+            r#"{
+                "message": "User has passed ID verification!",
+                "reference": "ref",
+                "response_time_ms": "95",
+                "status": "SUCCESS",
+                "success": true,
+                "entity_type": "individual",
+                "verification_status": "passed",
+                "kyc_level": "DEFAULT"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(response.success);
+        assert!(response.entity_type == Some(EntityType::Individual));
+        assert_eq!(response.verification_status.as_deref(), Some("passed"));
+        assert_eq!(
+            response.to_string(),
+            "CheckPartnerKycResponse(status: SUCCESS, verification_status: passed, kyc_level: DEFAULT)"
+        );
+
+        // an unknown handle fails with only the message
+        let response: CheckPartnerKycResponse = serde_json::from_str(
+            r#"{"status": "FAILURE", "success": false, "message": "unknown user handle"}"#,
+        )
+        .unwrap();
+
+        assert!(CheckPartnerKyc::is_failure(&response));
+        assert!(response.entity_type.is_none());
+        assert!(response.kyc_level.is_none());
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::entity::*;
+use crate::{send_multipart, Header, MultipartFile, RequiredSignatures, SilaEndpoint};
+
+// Uploads a supporting document for KYC. The message is the signed "data" part of a multipart
+// body; sign multipart_message(&message, &[file]) so the file's hash is part of what is signed.
+
+#[derive(Deserialize, Serialize)]
+pub struct DocumentMessage {
+    pub header: Header,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub filename: String,
+    pub mime_type: String,
+    pub document_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Default)]
+pub struct DocumentMessageParams {
+    pub sila_handle: String,
+    pub name: Option<String>,
+    pub filename: String,
+    pub mime_type: String,
+    pub document_type: String,
+    pub identity_type: Option<String>,
+    pub description: Option<String>,
+}
+
+impl From<DocumentMessageParams> for DocumentMessage {
+    fn from(params: DocumentMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        DocumentMessage {
+            header: header_message.header,
+            name: params.name,
+            filename: params.filename,
+            mime_type: params.mime_type,
+            document_type: params.document_type,
+            identity_type: params.identity_type,
+            description: params.description,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DocumentResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub reference_id: Option<String>,
+    pub document_id: Option<String>,
+}
+
+pub struct UploadDocument;
+
+impl SilaEndpoint for UploadDocument {
+    type Message = DocumentMessage;
+    type Response = DocumentResponse;
+
+    const PATH: &'static str = "documents";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &DocumentResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn upload_document(
+    params: &SignedMessageParams,
+    file: MultipartFile,
+) -> Result<DocumentResponse, Box<dyn std::error::Error + Sync + Send>> {
+    send_multipart::<UploadDocument>(params, vec![file]).await
+}
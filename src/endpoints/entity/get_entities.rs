@@ -0,0 +1,167 @@
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+use crate::endpoints::entity::*;
+use crate::{
+    execute_with_query, paginate, Header, Paginated, RequiredSignatures, Signatures, SilaEndpoint,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct GetEntitiesMessage {
+    pub header: Header,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_type: Option<EntityType>,
+}
+
+// page and per_page go in the query string rather than the signed message, so they are
+// arguments to get_entities instead of fields here.
+#[derive(Clone, Default)]
+pub struct GetEntitiesMessageParams {
+    pub entity_type: Option<EntityType>,
+}
+
+impl From<GetEntitiesMessageParams> for GetEntitiesMessage {
+    fn from(params: GetEntitiesMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        GetEntitiesMessage {
+            header: header_message.header,
+            message: "header_msg".to_string(),
+            entity_type: params.entity_type,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct IndividualEntity {
+    pub handle: Option<String>,
+    pub full_name: Option<String>,
+    pub created: Option<String>,
+    pub created_epoch: Option<i64>,
+    pub status: Option<String>,
+    pub blockchain_addresses: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BusinessEntity {
+    pub handle: Option<String>,
+    pub full_name: Option<String>,
+    pub created: Option<String>,
+    pub created_epoch: Option<i64>,
+    pub status: Option<String>,
+    pub blockchain_addresses: Option<Vec<String>>,
+    pub uuid: Option<String>,
+    pub business_type: Option<String>,
+    pub dba: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct EntityLists {
+    #[serde(default)]
+    pub individuals: Vec<IndividualEntity>,
+    #[serde(default)]
+    pub businesses: Vec<BusinessEntity>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EntityPagination {
+    pub returned_count: Option<i32>,
+    pub total_count: Option<i32>,
+    pub current_page: Option<i32>,
+    pub total_pages: Option<i32>,
+}
+
+impl std::fmt::Display for EntityPagination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", serde_json::to_string(self).unwrap())
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct GetEntitiesResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub entities: Option<EntityLists>,
+    pub pagination: Option<EntityPagination>,
+}
+
+impl Paginated for GetEntitiesResponse {
+    fn current_page(&self) -> Option<i32> {
+        self.pagination.as_ref().and_then(|x| x.current_page)
+    }
+
+    fn total_pages(&self) -> Option<i32> {
+        self.pagination.as_ref().and_then(|x| x.total_pages)
+    }
+}
+
+pub struct GetEntities;
+
+impl SilaEndpoint for GetEntities {
+    type Message = GetEntitiesMessage;
+    type Response = GetEntitiesResponse;
+
+    const PATH: &'static str = "get_entities";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::App;
+
+    fn is_failure(response: &GetEntitiesResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn get_entities(
+    params: &SignedMessageParams,
+    page: Option<i32>,
+    per_page: Option<i32>,
+) -> Result<GetEntitiesResponse, Box<dyn std::error::Error + Sync + Send>> {
+    let mut query: Vec<(&str, String)> = Vec::new();
+    if let Some(x) = page {
+        query.push(("page", x.to_string()));
+    }
+    if let Some(x) = per_page {
+        query.push(("per_page", x.to_string()));
+    }
+
+    execute_with_query::<GetEntities>(params, &query).await
+}
+
+// Every page of get_entities from the first, signing a fresh message for each page.
+pub fn get_entities_stream<F, Fut>(
+    params: GetEntitiesMessageParams,
+    per_page: Option<i32>,
+    sign: F,
+) -> impl Stream<Item = Result<GetEntitiesResponse, Box<dyn std::error::Error + Sync + Send>>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Signatures>,
+{
+    paginate::<GetEntities, _, _>(1, move |page| {
+        let message = serde_json::to_string(&GetEntitiesMessage::from(params.clone()));
+        let signing = message.map(|x| (sign(x.clone()), x));
+
+        async move {
+            let (signing, message) = signing?;
+            let signatures = signing.await;
+
+            get_entities(
+                &SignedMessageParams {
+                    sila_handle: Option::None,
+                    message,
+                    usersignature: signatures.usersignature,
+                    authsignature: signatures.authsignature,
+                },
+                Option::from(page),
+                per_page,
+            )
+            .await
+        }
+    })
+}
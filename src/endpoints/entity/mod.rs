@@ -1,15 +1,21 @@
 pub mod check_kyc;
+pub mod check_partner_kyc;
+pub mod documents;
+pub mod get_entities;
 pub mod register;
 pub mod request_kyc;
+pub mod sms;
 pub mod update;
 
-use crate::{header_message, HeaderMessage, SignedMessageParams, Status};
+use crate::{
+    execute, header_message, HeaderMessage, RequiredSignatures, SignedMessageParams, SilaEndpoint,
+    Status,
+};
 
-use log::error;
 use serde::{Deserialize, Serialize};
 use web3::{types::H160, types::H256};
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Default, Clone)]
 pub struct Address {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub added_epoch: Option<i64>,
@@ -35,56 +41,68 @@ pub struct Address {
     pub country: Option<String>,
 }
 
-impl Default for Address {
-    fn default() -> Self {
-        Address {
-            added_epoch: Option::None,
-            modified_epoch: Option::None,
-            nickname: Option::None,
-            uuid: Option::None,
-            address_alias: Option::None,
-            street_address_1: Option::None,
-            street_address_2: Option::None,
-            city: Option::None,
-            state: Option::None,
-            postal_code: Option::None,
-            country: Option::None,
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityType {
+    Individual,
+    Business,
+}
+
+impl std::fmt::Display for EntityType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            EntityType::Individual => write!(f, "individual"),
+            EntityType::Business => write!(f, "business"),
         }
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum IdentityAlias {
     Ssn,
     Ein
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Identity {
     pub identity_alias: IdentityAlias,
     pub identity_value: String,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Default, Clone)]
 pub struct Contact {
-    pub phone: String,
-    pub contact_alias: String,
-    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_alias: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct CryptoEntry {
-    pub crypto_alias: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crypto_alias: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub crypto_status: Option<String>,
     pub crypto_address: String,
     pub crypto_code: String,
 }
 
+#[derive(Deserialize, Serialize, Default, Clone)]
+pub struct Device {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_identifier: Option<String>,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Entity {
-    pub birthdate: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birthdate: Option<String>,
     pub entity_name: String,
     pub first_name: String,
     pub last_name: String,
@@ -120,6 +138,17 @@ pub struct PhoneResponse {
     pub primary: Option<bool>,
 }
 
+impl PhoneResponse {
+    pub fn is_confirmed(&self) -> bool {
+        self.sms_confirmed.unwrap_or(false)
+    }
+
+    // A confirmation code was sent but has not been entered yet.
+    pub fn is_awaiting_confirmation(&self) -> bool {
+        self.sms_confirmation_requested.unwrap_or(false) && !self.is_confirmed()
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct DeviceResponse {
     pub added_epoch: Option<i64>,
@@ -137,6 +166,26 @@ pub struct MembershipResponse {
     pub certification_token: Option<String>,
 }
 
+// The entity block of get_entity; businesses have no names or birthdate, so unlike the Entity
+// sent with register every field is optional.
+#[derive(Deserialize, Serialize)]
+pub struct EntityResponse {
+    pub created_epoch: Option<i64>,
+    pub entity_name: Option<String>,
+    pub birthdate: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub relationship: Option<String>,
+    #[serde(rename = "type")]
+    pub entity_type: Option<EntityType>,
+    pub business_type: Option<String>,
+    pub naics_code: Option<i32>,
+    pub naics_category: Option<String>,
+    pub naics_subcategory: Option<String>,
+    pub business_website: Option<String>,
+    pub doing_business_as: Option<String>,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct GetEntityResponse {
     pub success: bool,
@@ -144,8 +193,8 @@ pub struct GetEntityResponse {
     pub reference: Option<String>,
     pub response_time_ms: Option<String>,
     pub user_handle: Option<String>,
-    pub entity_type: Option<String>,
-    pub entity: Option<Entity>,
+    pub entity_type: Option<EntityType>,
+    pub entity: Option<EntityResponse>,
     pub addresses: Option<Vec<Address>>,
     pub identities: Option<Vec<IdentityResponse>>,
     pub emails: Option<Vec<EmailResponse>>,
@@ -154,6 +203,16 @@ pub struct GetEntityResponse {
     pub memberships: Option<Vec<MembershipResponse>>,
 }
 
+impl GetEntityResponse {
+    pub fn confirmed_phones(&self) -> Vec<&PhoneResponse> {
+        self.phones.iter().flatten().filter(|x| x.is_confirmed()).collect()
+    }
+
+    pub fn unconfirmed_phones(&self) -> Vec<&PhoneResponse> {
+        self.phones.iter().flatten().filter(|x| !x.is_confirmed()).collect()
+    }
+}
+
 pub struct RequestEntityParams {
     pub sila_handle: String,
     pub ethereum_address: H160,
@@ -177,54 +236,26 @@ impl From<RequestEntityMessageParams> for HeaderMessage {
     }
 }
 
-pub async fn get_entity(
-    params: &SignedMessageParams,
-) -> Result<GetEntityResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
+pub struct GetEntity;
 
-    let _url: String = format!("{}/get_entity", sila_params.gateway);
-    let h: HeaderMessage = serde_json::from_str(&params.message.clone()).unwrap();
-
-    let client = reqwest::Client::new();
-    let resp: reqwest::Response;
-
-    match &params.usersignature {
-        Some(x) => {
-            resp = client
-                .post(&_url.to_owned())
-                .header("usersignature", x)
-                .header("authsignature", params.authsignature.clone())
-                .json(&h)
-                .send()
-                .await?;
-        }
-        None => {
-            resp = client
-                .post(&_url.to_owned())
-                .header("authsignature", params.authsignature.clone())
-                .json(&h)
-                .send()
-                .await?;
-        }
-    }
+impl SilaEndpoint for GetEntity {
+    type Message = HeaderMessage;
+    type Response = GetEntityResponse;
 
-    let response_text = resp.text().await?;
-    let response: Result<GetEntityResponse, serde_json::Error> =
-        serde_json::from_str(&response_text);
+    const PATH: &'static str = "get_entity";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::OptionalUser;
 
-    match response {
-        Ok(x) if x.success != true => {
-            error!("get_entity API Error: String({})", response_text);
-            Ok(x)
-        }
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("JSON Decoding Error: String({})", response_text);
-            Err(Box::from(e))
-        }
+    fn is_failure(response: &GetEntityResponse) -> bool {
+        !response.success
     }
 }
 
+pub async fn get_entity(
+    params: &SignedMessageParams,
+) -> Result<GetEntityResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<GetEntity>(params).await
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct CheckResponse {
     pub message: Option<String>,
@@ -261,49 +292,82 @@ pub async fn check_handle_message(
     Ok(serde_json::to_string(&header)?)
 }
 
+pub struct CheckHandle;
+
+impl SilaEndpoint for CheckHandle {
+    type Message = HeaderMessage;
+    type Response = CheckResponse;
+
+    const PATH: &'static str = "check_handle";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::OptionalUser;
+
+    fn is_failure(response: &CheckResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
 pub async fn check_handle(
     params: &SignedMessageParams,
 ) -> Result<CheckResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
-    let _url: String = format!("{}/check_handle", sila_params.gateway);
-
-    let h: HeaderMessage = serde_json::from_str(&params.message.clone()).unwrap();
-
-    let client = reqwest::Client::new();
-    let resp: reqwest::Response;
-
-    match &params.usersignature {
-        Some(x) => {
-            resp = client
-                .post(&_url.to_owned())
-                .header("usersignature", x)
-                .header("authsignature", params.authsignature.clone())
-                .json(&h)
-                .send()
-                .await?;
-        }
-        None => {
-            resp = client
-                .post(&_url.to_owned())
-                .header("authsignature", params.authsignature.clone())
-                .json(&h)
-                .send()
-                .await?;
-        }
-    }
+    execute::<CheckHandle>(params).await
+}
 
-    let response_text = resp.text().await?;
-    let response: Result<CheckResponse, serde_json::Error> = serde_json::from_str(&response_text);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_confirmed_phones() {
+        let response: GetEntityResponse = serde_json::from_str(
+            r#"{
+                "success": true,
+                "status": "SUCCESS",
+                "phones": [
+                    {"uuid": "a", "phone": "+15035550100", "sms_confirmation_requested": true, "sms_confirmed": true},
+                    {"uuid": "b", "phone": "+15035550101", "sms_confirmation_requested": true, "sms_confirmed": false},
+                    {"uuid": "c", "phone": "+15035550102"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let uuids = |phones: Vec<&PhoneResponse>| -> Vec<String> {
+            phones.into_iter().filter_map(|x| x.uuid.clone()).collect()
+        };
+
+        assert_eq!(uuids(response.confirmed_phones()), vec!["a"]);
+        assert_eq!(uuids(response.unconfirmed_phones()), vec!["b", "c"]);
+        assert!(response.phones.as_ref().unwrap()[1].is_awaiting_confirmation());
+        assert!(!response.phones.as_ref().unwrap()[2].is_awaiting_confirmation());
+    }
 
-    match response {
-        Ok(x) if x.status == Status::FAILURE => {
-            error!("check_handle failed: {}", response_text);
-            Ok(x)
-        }
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("check_handle json decode failed: {}", response_text);
-            Err(Box::from(e))
-        }
+    #[test]
+    fn decodes_a_business_without_names() {
+        let response: GetEntityResponse = serde_json::from_str(
+            r#"{
+                "success": true,
+                "status": "SUCCESS",
+                "entity_type": "business",
+                "entity": {
+                    "entity_name": "Holdings LLC",
+                    "birthdate": null,
+                    "type": "business",
+                    "business_type": "llc",
+                    "naics_code": 721,
+                    "is_sole_proprietor": false
+                },
+                "wallets": [{"blockchain_address": "0x1a642f0e3c3af545e7acbd38b07251b3990914f1"}]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(response.entity_type == Some(EntityType::Business));
+
+        let entity = response.entity.unwrap();
+        assert!(entity.entity_type == Some(EntityType::Business));
+        assert!(entity.first_name.is_none());
+        assert!(entity.birthdate.is_none());
+        assert_eq!(entity.business_type.as_deref(), Some("llc"));
+        assert_eq!(entity.naics_code, Some(721));
     }
 }
@@ -1,19 +1,26 @@
-use log::error;
 use serde::{Deserialize, Serialize};
 use web3::types::H160;
 
-use crate::endpoints::entity::{Address, Contact, CryptoEntry, Entity, Identity, IdentityAlias};
-use crate::{header_message, Header, HeaderMessage, SignedMessageParams, Status};
+use crate::endpoints::entity::{Address, Contact, CryptoEntry, Device, Entity, Identity, IdentityAlias};
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Status, Validate, ValidationErrors,
+};
 
 #[derive(Deserialize, Serialize)]
 pub struct RegisterMessage {
     pub header: Header,
     pub message: String,
-    pub address: Address,
-    pub identity: Identity,
-    pub contact: Contact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<Identity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<Contact>,
     pub crypto_entry: CryptoEntry,
     pub entity: Entity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<Device>,
 }
 
 #[derive(Clone)]
@@ -34,45 +41,180 @@ pub struct RegisterMessageParams {
 
 impl From<RegisterMessageParams> for RegisterMessage {
     fn from(params: RegisterMessageParams) -> Self {
+        RegistrationBuilder::new(&params.sila_handle, params.ethereum_address)
+            .entity_name("default")
+            .first_name(&params.first_name)
+            .last_name(&params.last_name)
+            .birthdate(&params.birthdate)
+            .address(Address {
+                address_alias: Option::from("default".to_string()),
+                street_address_1: Option::from(params.street_address_1.clone()),
+                city: Option::from(params.city.clone()),
+                state: Option::from(params.state.clone()),
+                postal_code: Option::from(params.postal_code.clone()),
+                country: Option::from("US".to_string()),
+                ..Default::default()
+            })
+            .ssn(&params.ssn)
+            .contact_alias("default")
+            .phone(&params.phone)
+            .email(&params.email)
+            .crypto_alias("default")
+            .message()
+    }
+}
+
+// Builds a RegisterMessage with only the sections a user actually has; KYC-LITE registrations,
+// for instance, can leave out the address and identity. Sections that are given are validated
+// as fully as RegisterMessageParams is.
+#[derive(Clone)]
+pub struct RegistrationBuilder {
+    sila_handle: String,
+    ethereum_address: H160,
+    crypto_alias: Option<String>,
+    entity_name: Option<String>,
+    first_name: String,
+    last_name: String,
+    birthdate: Option<String>,
+    relationship: Option<String>,
+    address: Option<Address>,
+    identity: Option<Identity>,
+    contact: Contact,
+    device: Option<Device>,
+}
+
+impl RegistrationBuilder {
+    pub fn new(sila_handle: &str, ethereum_address: H160) -> Self {
+        RegistrationBuilder {
+            sila_handle: sila_handle.to_string(),
+            ethereum_address,
+            crypto_alias: Option::None,
+            entity_name: Option::None,
+            first_name: String::new(),
+            last_name: String::new(),
+            birthdate: Option::None,
+            relationship: Option::from("user".to_string()),
+            address: Option::None,
+            identity: Option::None,
+            contact: Contact::default(),
+            device: Option::None,
+        }
+    }
+
+    pub fn first_name(mut self, first_name: &str) -> Self {
+        self.first_name = first_name.to_string();
+        self
+    }
+
+    pub fn last_name(mut self, last_name: &str) -> Self {
+        self.last_name = last_name.to_string();
+        self
+    }
+
+    // Defaults to "first_name last_name".
+    pub fn entity_name(mut self, entity_name: &str) -> Self {
+        self.entity_name = Option::from(entity_name.to_string());
+        self
+    }
+
+    pub fn birthdate(mut self, birthdate: &str) -> Self {
+        self.birthdate = Option::from(birthdate.to_string());
+        self
+    }
+
+    pub fn relationship(mut self, relationship: &str) -> Self {
+        self.relationship = Option::from(relationship.to_string());
+        self
+    }
+
+    // Alias, nickname, street_address_2 and country are sent as given.
+    pub fn address(mut self, address: Address) -> Self {
+        self.address = Option::from(address);
+        self
+    }
+
+    pub fn ssn(self, ssn: &str) -> Self {
+        self.identity(IdentityAlias::Ssn, ssn)
+    }
+
+    pub fn identity(mut self, identity_alias: IdentityAlias, identity_value: &str) -> Self {
+        self.identity = Option::from(Identity {
+            identity_alias,
+            identity_value: identity_value.to_string(),
+        });
+        self
+    }
+
+    pub fn phone(mut self, phone: &str) -> Self {
+        self.contact.phone = Option::from(phone.to_string());
+        self
+    }
+
+    pub fn email(mut self, email: &str) -> Self {
+        self.contact.email = Option::from(email.to_string());
+        self
+    }
+
+    pub fn contact_alias(mut self, contact_alias: &str) -> Self {
+        self.contact.contact_alias = Option::from(contact_alias.to_string());
+        self
+    }
+
+    pub fn crypto_alias(mut self, crypto_alias: &str) -> Self {
+        self.crypto_alias = Option::from(crypto_alias.to_string());
+        self
+    }
+
+    pub fn device(mut self, device: Device) -> Self {
+        self.device = Option::from(device);
+        self
+    }
+
+    pub fn build(self) -> Result<RegisterMessage, ValidationErrors> {
+        let message = self.message();
+        message.validate()?;
+        Ok(message)
+    }
+
+    fn message(self) -> RegisterMessage {
         let sila_params = &*crate::SILA_PARAMS;
 
         let mut header_message: HeaderMessage = header_message();
-        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.user_handle = Option::from(self.sila_handle.clone());
         header_message.header.auth_handle = sila_params.app_handle.clone();
 
+        let entity_name = self
+            .entity_name
+            .unwrap_or_else(|| format!("{} {}", self.first_name, self.last_name).trim().to_string());
+
+        let contact = match self.contact {
+            Contact {
+                phone: None,
+                email: None,
+                ..
+            } => Option::None,
+            x => Option::from(x),
+        };
+
         RegisterMessage {
             header: header_message.header,
             entity: Entity {
-                relationship: Option::from("user".to_string()),
-                entity_name: "default".to_string(),
-                first_name: params.first_name.clone(),
-                last_name: params.last_name.clone(),
-                birthdate: params.birthdate.clone(),
-            },
-            address: Address {
-                address_alias: Option::from("default".to_string()),
-                street_address_1: Option::from(params.street_address_1.clone()),
-                city: Option::from(params.city.clone()),
-                state: Option::from(params.state.clone()),
-                postal_code: Option::from(params.postal_code.clone()),
-                country: Option::from("US".to_string()),
-                ..Default::default()
-            },
-            identity: Identity {
-                identity_alias: IdentityAlias::Ssn,
-                identity_value: params.ssn.clone(),
-            },
-            contact: Contact {
-                contact_alias: "default".to_string(),
-                phone: params.phone.clone(),
-                email: params.email.clone(),
+                relationship: self.relationship,
+                entity_name,
+                first_name: self.first_name,
+                last_name: self.last_name,
+                birthdate: self.birthdate,
             },
+            address: self.address,
+            identity: self.identity,
+            contact,
             crypto_entry: CryptoEntry {
-                crypto_alias: "default".to_string(),
+                crypto_alias: self.crypto_alias,
                 crypto_status: Option::None,
-                crypto_address: format!("{:#x}", params.ethereum_address.clone()),
+                crypto_address: format!("{:#x}", self.ethereum_address),
                 crypto_code: "ETH".to_string(),
             },
+            device: self.device,
             message: "entity_msg".to_string(),
         }
     }
@@ -85,36 +227,26 @@ pub struct RegisterResponse {
     pub status: Status,
 }
 
+pub struct Register;
+
+impl SilaEndpoint for Register {
+    type Message = RegisterMessage;
+    type Response = RegisterResponse;
+
+    const PATH: &'static str = "register";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn check(message: &RegisterMessage) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        Ok(message.validate()?)
+    }
+
+    fn is_failure(response: &RegisterResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
 pub async fn register(
     params: &SignedMessageParams,
 ) -> Result<RegisterResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
-    let _url: String = format!("{}/register", sila_params.gateway);
-
-    let h: RegisterMessage = serde_json::from_str(&params.message.clone()).unwrap();
-
-    let client = reqwest::Client::new();
-    let resp = client
-        .post(&_url.to_owned())
-        .header("usersignature", params.usersignature.clone().unwrap())
-        .header("authsignature", params.authsignature.clone())
-        .json(&h)
-        .send()
-        .await?;
-
-    let response_text = resp.text().await?;
-    let response: Result<RegisterResponse, serde_json::Error> =
-        serde_json::from_str(&response_text);
-
-    match response {
-        Ok(x) if x.status == Status::FAILURE => {
-            error!("general register error | text: {}", response_text);
-            Ok(x)
-        }
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("decoding error | text: {}", response_text);
-            Err(Box::from(e))
-        }
-    }
+    execute::<Register>(params).await
 }
@@ -1,7 +1,7 @@
-use log::error;
 use serde::{Deserialize, Serialize};
 
 use crate::endpoints::entity::*;
+use crate::{execute, RequiredSignatures, SilaEndpoint};
 
 #[derive(Serialize, Deserialize)]
 pub struct RequestKycResponse {
@@ -28,36 +28,22 @@ impl From<RequestKycMessageParams> for HeaderMessage {
     }
 }
 
+pub struct RequestKyc;
+
+impl SilaEndpoint for RequestKyc {
+    type Message = HeaderMessage;
+    type Response = RequestKycResponse;
+
+    const PATH: &'static str = "request_kyc";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &RequestKycResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
 pub async fn request_kyc(
     params: &SignedMessageParams,
 ) -> Result<RequestKycResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
-
-    let _url: String = format!("{}/request_kyc", sila_params.gateway);
-
-    let h: HeaderMessage = serde_json::from_str(&params.message.clone()).unwrap();
-
-    let client = reqwest::Client::new();
-    let resp = client
-        .post(&_url.to_owned())
-        .header("usersignature", params.usersignature.clone().unwrap())
-        .header("authsignature", params.authsignature.clone())
-        .json(&h)
-        .send()
-        .await?;
-    let response_text = resp.text().await?;
-    let response: Result<RequestKycResponse, serde_json::Error> =
-        serde_json::from_str(&response_text);
-
-    match response {
-        Ok(x) if x.status == Status::FAILURE => {
-            error!("request_kyc error: {}", response_text);
-            Ok(x)
-        }
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("request_kyc response error: {}", response_text);
-            Err(Box::from(e))
-        }
-    }
+    execute::<RequestKyc>(params).await
 }
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::entity::*;
+use crate::{execute, Header, RequiredSignatures, SilaEndpoint, Validate};
+
+// A phone added or updated with sms_opt_in is unconfirmed until the user enters the code Sila
+// texts them: request_sms_confirmation sends the code and confirm_sms submits it.
+
+#[derive(Deserialize, Serialize)]
+pub struct SmsConfirmationResponse {
+    pub success: bool,
+    pub status: Option<String>,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub phone: Option<PhoneResponse>,
+}
+
+#[derive(Clone)]
+pub struct SmsConfirmationMessageParams {
+    pub sila_handle: String,
+    pub phone_uuid: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SmsConfirmationMessage {
+    pub header: Header,
+    pub uuid: String,
+}
+
+impl From<SmsConfirmationMessageParams> for SmsConfirmationMessage {
+    fn from(params: SmsConfirmationMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        SmsConfirmationMessage {
+            header: header_message.header,
+            uuid: params.phone_uuid.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConfirmSmsMessageParams {
+    pub sila_handle: String,
+    pub phone_uuid: String,
+    pub code: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ConfirmSmsMessage {
+    pub header: Header,
+    pub uuid: String,
+    pub code: String,
+}
+
+impl From<ConfirmSmsMessageParams> for ConfirmSmsMessage {
+    fn from(params: ConfirmSmsMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        ConfirmSmsMessage {
+            header: header_message.header,
+            uuid: params.phone_uuid.clone(),
+            code: params.code.trim().to_string(),
+        }
+    }
+}
+
+pub struct RequestSmsConfirmation;
+
+impl SilaEndpoint for RequestSmsConfirmation {
+    type Message = SmsConfirmationMessage;
+    type Response = SmsConfirmationResponse;
+
+    const PATH: &'static str = "request_sms_confirmation";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &SmsConfirmationResponse) -> bool {
+        !response.success
+    }
+}
+
+pub async fn request_sms_confirmation(
+    params: &SignedMessageParams,
+) -> Result<SmsConfirmationResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<RequestSmsConfirmation>(params).await
+}
+
+pub struct ConfirmSms;
+
+impl SilaEndpoint for ConfirmSms {
+    type Message = ConfirmSmsMessage;
+    type Response = SmsConfirmationResponse;
+
+    const PATH: &'static str = "confirm_sms";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn check(message: &ConfirmSmsMessage) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        Ok(message.validate()?)
+    }
+
+    fn is_failure(response: &SmsConfirmationResponse) -> bool {
+        !response.success
+    }
+}
+
+pub async fn confirm_sms(
+    params: &SignedMessageParams,
+) -> Result<SmsConfirmationResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<ConfirmSms>(params).await
+}
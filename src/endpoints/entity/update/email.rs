@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
-use log::error;
 use web3::types::H160;
 
-use crate::{header_message, Header, HeaderMessage, SignedMessageParams};
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Validate,
+};
 use crate::endpoints::entity::*;
 
 #[derive(Clone)]
@@ -46,34 +48,26 @@ impl From<UpdateEmailMessageParams> for UpdateEmailMessage {
     }
 }
 
-pub async fn update_email(params: &SignedMessageParams) -> Result<UpdateEmailResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
+pub struct UpdateEmail;
 
-    let _url: String = format!("{}/update/email", sila_params.gateway);
+impl SilaEndpoint for UpdateEmail {
+    type Message = UpdateEmailMessage;
+    type Response = UpdateEmailResponse;
 
-    let h: UpdateEmailMessage = serde_json::from_str(&params.message.clone()).unwrap();
+    const PATH: &'static str = "update/email";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
 
-    let client = reqwest::Client::new();
-    let resp = client
-        .post(&_url.to_owned())
-        .header("usersignature", params.usersignature.clone().unwrap())
-        .header("authsignature", params.authsignature.clone())
-        .json(&h)
-        .send()
-        .await?;
-    
-    let response_text = resp.text().await?;
-    let response : Result<UpdateEmailResponse, serde_json::Error> = serde_json::from_str(&response_text);
+    fn check(message: &UpdateEmailMessage) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        Ok(message.validate()?)
+    }
 
-    match response {
-        Ok(x) if x.success != true => {
-            error!("update_email API Error: String({})", response_text);
-            Ok(x)
-        },
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("JSON Decoding Error: String({})", response_text);
-            Err(Box::from(e))
-        }
+    fn is_failure(response: &UpdateEmailResponse) -> bool {
+        !response.success
     }
-}
\ No newline at end of file
+}
+
+pub async fn update_email(
+    params: &SignedMessageParams,
+) -> Result<UpdateEmailResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<UpdateEmail>(params).await
+}
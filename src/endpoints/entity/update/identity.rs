@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
-use log::error;
 use web3::types::H160;
 
-use crate::{header_message, Header, HeaderMessage};
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SilaEndpoint, Validate,
+};
 use crate::endpoints::entity::*;
 
 #[derive(Clone)]
@@ -49,34 +50,26 @@ impl From<UpdateIdentityMessageParams> for UpdateIdentityMessage {
     }
 }
 
-pub async fn update_identity(params: &SignedMessageParams) -> Result<UpdateIdentityResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
+pub struct UpdateIdentity;
 
-    let _url: String = format!("{}/update/identity", sila_params.gateway);
+impl SilaEndpoint for UpdateIdentity {
+    type Message = UpdateIdentityMessage;
+    type Response = UpdateIdentityResponse;
 
-    let h: UpdateIdentityMessage = serde_json::from_str(&params.message.clone()).unwrap();
+    const PATH: &'static str = "update/identity";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
 
-    let client = reqwest::Client::new();
-    let resp = client
-        .post(&_url.to_owned())
-        .header("usersignature", params.usersignature.clone().unwrap())
-        .header("authsignature", params.authsignature.clone())
-        .json(&h)
-        .send()
-        .await?;
-    
-    let response_text = resp.text().await?;
-    let response : Result<UpdateIdentityResponse, serde_json::Error> = serde_json::from_str(&response_text);
+    fn check(message: &UpdateIdentityMessage) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        Ok(message.validate()?)
+    }
 
-    match response {
-        Ok(x) if x.success != true => {
-            error!("update_identity API Error: String({})", response_text);
-            Ok(x)
-        },
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("JSON Decoding Error: String({})", response_text);
-            Err(Box::from(e))
-        }
+    fn is_failure(response: &UpdateIdentityResponse) -> bool {
+        !response.success
     }
-}
\ No newline at end of file
+}
+
+pub async fn update_identity(
+    params: &SignedMessageParams,
+) -> Result<UpdateIdentityResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<UpdateIdentity>(params).await
+}
@@ -3,7 +3,10 @@ use web3::{
     types::H160,
 };
 
-use crate::{header_message, Header, HeaderMessage, SignedMessageParams };
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Validate,
+};
 use crate::endpoints::entity::*;
 
 #[derive(Clone)]
@@ -11,10 +14,43 @@ pub struct UpdatePhoneMessageParams {
     pub sila_handle: String,
     pub ethereum_address: H160,
     pub uuid: String,
-    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub sms_opt_in: Option<bool>
+}
+
+// sms_opt_in asks Sila to send SMS notifications to the number; it has to be confirmed through
+// request_sms_confirmation and confirm_sms before any are sent.
+#[derive(Clone)]
+pub struct AddPhoneMessageParams {
+    pub sila_handle: String,
+    pub phone: String,
     pub sms_opt_in: Option<bool>
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct AddPhoneMessage {
+    pub header: Header,
+    pub phone: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sms_opt_in: Option<bool>
+}
+
+impl From<AddPhoneMessageParams> for AddPhoneMessage {
+    fn from(params: AddPhoneMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        AddPhoneMessage {
+            header: header_message.header,
+            phone: params.phone.clone(),
+            sms_opt_in: params.sms_opt_in
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct UpdatePhoneResponse {
     pub success: bool,
@@ -46,40 +82,56 @@ impl From<UpdatePhoneMessageParams> for UpdatePhoneMessage {
         UpdatePhoneMessage {
             header: header_message.header,
             uuid: params.uuid.clone(),
-            phone: params.email.clone(),
-            sms_opt_in: params.sms_opt_in.clone()
+            phone: params.phone.clone(),
+            sms_opt_in: params.sms_opt_in
         }
     }
 }
 
-pub async fn update_phone(params: &SignedMessageParams) -> Result<UpdatePhoneResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
-
-    let _url: String = format!("{}/update/email", sila_params.gateway);
-
-    let h: UpdatePhoneMessage = serde_json::from_str(&params.message.clone()).unwrap();
-
-    let client = reqwest::Client::new();
-    let resp = client
-        .post(&_url.to_owned())
-        .header("usersignature", params.usersignature.clone().unwrap())
-        .header("authsignature", params.authsignature.clone())
-        .json(&h)
-        .send()
-        .await?;
-    
-    let response_text = resp.text().await?;
-    let response : Result<UpdatePhoneResponse, serde_json::Error> = serde_json::from_str(&response_text);
-
-    match response {
-        Ok(x) if x.success != true => {
-            error!("update_phone API Error: String({})", response_text);
-            Ok(x)
-        },
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("JSON Decoding Error: String({})", response_text);
-            Err(Box::from(e))
-        }
+pub struct UpdatePhone;
+
+impl SilaEndpoint for UpdatePhone {
+    type Message = UpdatePhoneMessage;
+    type Response = UpdatePhoneResponse;
+
+    const PATH: &'static str = "update/phone";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn check(message: &UpdatePhoneMessage) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        Ok(message.validate()?)
     }
-}
\ No newline at end of file
+
+    fn is_failure(response: &UpdatePhoneResponse) -> bool {
+        !response.success
+    }
+}
+
+pub async fn update_phone(
+    params: &SignedMessageParams,
+) -> Result<UpdatePhoneResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<UpdatePhone>(params).await
+}
+
+pub struct AddPhone;
+
+impl SilaEndpoint for AddPhone {
+    type Message = AddPhoneMessage;
+    type Response = UpdatePhoneResponse;
+
+    const PATH: &'static str = "add/phone";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn check(message: &AddPhoneMessage) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        Ok(message.validate()?)
+    }
+
+    fn is_failure(response: &UpdatePhoneResponse) -> bool {
+        !response.success
+    }
+}
+
+pub async fn add_phone(
+    params: &SignedMessageParams,
+) -> Result<UpdatePhoneResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<AddPhone>(params).await
+}
@@ -1,4 +1,9 @@
+pub mod endpoint;
+pub mod pagination;
 pub mod entity;
 pub mod account;
+pub mod card;
 pub mod wallet;
-pub mod transaction;
\ No newline at end of file
+pub mod transaction;
+pub mod virtual_account;
+pub mod webhook;
\ No newline at end of file
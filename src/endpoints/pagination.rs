@@ -0,0 +1,106 @@
+use futures::{stream, Stream};
+use std::future::Future;
+
+use crate::SilaEndpoint;
+
+// A listing response that reports where it sits in the pages Sila has for the query.
+pub trait Paginated {
+    fn current_page(&self) -> Option<i32>;
+    fn total_pages(&self) -> Option<i32>;
+}
+
+// Walks a listing from first_page, calling fetch for each page only as the stream is polled, so
+// dropping the stream (or using take/take_while) ends the walk without fetching the rest. Each
+// page is a separate request, so fetch signs a fresh message for it. A page Sila marks as failed
+// ends the walk with an error; execute has already logged the response.
+pub fn paginate<E, F, Fut>(
+    first_page: i32,
+    fetch: F,
+) -> impl Stream<Item = Result<E::Response, Box<dyn std::error::Error + Sync + Send>>>
+where
+    E: SilaEndpoint,
+    E::Response: Paginated,
+    F: FnMut(i32) -> Fut,
+    Fut: Future<Output = Result<E::Response, Box<dyn std::error::Error + Sync + Send>>>,
+{
+    stream::try_unfold((fetch, Option::from(first_page)), |(mut fetch, page)| async move {
+        let page = match page {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+
+        let response = fetch(page).await?;
+
+        if E::is_failure(&response) {
+            return Err(Box::from(format!("{} failed on page {}", E::PATH, page)));
+        }
+
+        let next_page = match response.total_pages() {
+            Some(x) if response.current_page().unwrap_or(page) < x => Option::from(page + 1),
+            _ => Option::None,
+        };
+
+        Ok(Some((response, (fetch, next_page))))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RequiredSignatures;
+    use futures::executor::block_on;
+    use futures::{StreamExt, TryStreamExt};
+    use std::cell::RefCell;
+
+    struct Listing;
+
+    #[derive(serde::Deserialize)]
+    struct Page {
+        page: i32,
+        total_pages: i32,
+        success: bool,
+    }
+
+    impl Paginated for Page {
+        fn current_page(&self) -> Option<i32> {
+            Option::from(self.page)
+        }
+
+        fn total_pages(&self) -> Option<i32> {
+            Option::from(self.total_pages)
+        }
+    }
+
+    impl SilaEndpoint for Listing {
+        type Message = serde_json::Value;
+        type Response = Page;
+
+        const PATH: &'static str = "listing";
+        const SIGNATURES: RequiredSignatures = RequiredSignatures::App;
+
+        fn is_failure(response: &Page) -> bool {
+            !response.success
+        }
+    }
+
+    #[test]
+    fn walks_pages_lazily_and_stops_on_failure() {
+        let fetched = RefCell::new(Vec::new());
+        let fetch = |page: i32| {
+            fetched.borrow_mut().push(page);
+            async move { Ok(Page { page, total_pages: 3, success: page != 4 }) }
+        };
+
+        let pages: Vec<i32> =
+            block_on(paginate::<Listing, _, _>(2, fetch).map_ok(|x| x.page).try_collect()).unwrap();
+        assert_eq!(pages, vec![2, 3]);
+
+        block_on(paginate::<Listing, _, _>(1, fetch).take(1).collect::<Vec<_>>());
+        assert_eq!(*fetched.borrow(), vec![2, 3, 1]);
+
+        let error = block_on(paginate::<Listing, _, _>(4, fetch).try_collect::<Vec<_>>())
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "listing failed on page 4");
+    }
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Status,
+};
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WireStatus {
+    PendingApproval,
+    Approved,
+    Rejected,
+    Submitted,
+    Completed,
+    Failed,
+    // e.g. a new step in the approval flow; the approve_wire response still decodes
+    #[serde(other)]
+    Unknown,
+}
+
+impl std::fmt::Display for WireStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            WireStatus::PendingApproval => write!(f, "pending_approval"),
+            WireStatus::Approved => write!(f, "approved"),
+            WireStatus::Rejected => write!(f, "rejected"),
+            WireStatus::Submitted => write!(f, "submitted"),
+            WireStatus::Completed => write!(f, "completed"),
+            WireStatus::Failed => write!(f, "failed"),
+            WireStatus::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ApproveWireMessage {
+    pub header: Header,
+    pub transaction_id: String,
+    pub approve: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mock_wire_account_name: Option<String>,
+}
+
+#[derive(Clone, Default)]
+pub struct ApproveWireMessageParams {
+    pub sila_handle: Option<String>,
+    pub transaction_id: String,
+    pub approve: bool,
+    pub notes: Option<String>,
+    pub mock_wire_account_name: Option<String>,
+    pub reference: Option<String>,
+}
+
+impl From<ApproveWireMessageParams> for ApproveWireMessage {
+    fn from(params: ApproveWireMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = params.sila_handle.clone();
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        if let Some(reference) = params.reference {
+            header_message.header.reference = reference;
+        }
+
+        ApproveWireMessage {
+            header: header_message.header,
+            transaction_id: params.transaction_id,
+            approve: params.approve,
+            notes: params.notes,
+            mock_wire_account_name: params.mock_wire_account_name,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ApproveWireResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub transaction_id: Option<String>,
+    pub wire_status: Option<WireStatus>,
+}
+
+impl std::fmt::Display for ApproveWireResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ApproveWireResponse(transaction_id: {}, wire_status: {}, status: {})",
+            self.transaction_id.as_ref().unwrap_or(&"none".to_string()),
+            self.wire_status.as_ref().map(|x| x.to_string()).unwrap_or_else(|| "none".to_string()),
+            self.status
+        )
+    }
+}
+
+pub struct ApproveWire;
+
+impl SilaEndpoint for ApproveWire {
+    type Message = ApproveWireMessage;
+    type Response = ApproveWireResponse;
+
+    const PATH: &'static str = "approve_wire";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::OptionalUser;
+
+    fn is_failure(response: &ApproveWireResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn approve_wire(
+    params: &SignedMessageParams,
+) -> Result<ApproveWireResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<ApproveWire>(params).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_wire_statuses_added_later() {
+        let response: ApproveWireResponse = serde_json::from_str(
+            r#"{"success": true, "status": "SUCCESS", "message": "ok", "wire_status": "on_hold"}"#,
+        )
+        .unwrap();
+
+        assert!(response.wire_status == Some(WireStatus::Unknown));
+    }
+}
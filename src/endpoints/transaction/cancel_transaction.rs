@@ -1,9 +1,7 @@
-use crate::header_message;
+use crate::{execute, header_message, RequiredSignatures, SilaEndpoint};
 use crate::Header;
 use crate::HeaderMessage;
-use log::error;
 use serde::{Deserialize, Serialize};
-use web3::types::H160;
 
 use crate::{SignedMessageParams, Status};
 
@@ -13,24 +11,13 @@ pub struct CancelTransactionMessage {
     pub transaction_id: String,
 }
 
+#[derive(Default)]
 pub struct CancelTransactionMessageParams {
     pub sila_handle: String,
-    pub ethereum_address: H160,
     pub transaction_id: String,
     pub reference: Option<String>
 }
 
-impl Default for CancelTransactionMessageParams {
-    fn default() -> Self {
-        CancelTransactionMessageParams {
-            sila_handle: String::new(),
-            ethereum_address: H160::zero(),
-            transaction_id: String::new(),
-            reference: Option::None,
-        }
-    }
-}
-
 impl From<CancelTransactionMessageParams> for CancelTransactionMessage {
     fn from(params: CancelTransactionMessageParams) -> Self {
         let sila_params = &*crate::SILA_PARAMS;
@@ -39,8 +26,8 @@ impl From<CancelTransactionMessageParams> for CancelTransactionMessage {
         header_message.header.user_handle = Option::from(params.sila_handle.clone());
         header_message.header.auth_handle = sila_params.app_handle.clone();
 
-        if params.reference.is_some() {
-            header_message.header.reference = params.reference.unwrap();
+        if let Some(reference) = params.reference {
+            header_message.header.reference = reference;
         }
 
         CancelTransactionMessage {
@@ -58,39 +45,22 @@ pub struct CancelTransactionResponse {
     pub success: bool,
 }
 
-pub async fn cancel_transaction(
-    params: &SignedMessageParams,
-) -> Result<CancelTransactionResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
-    let _url: String = format!("{}/cancel_transaction", sila_params.gateway);
-
-    let h: CancelTransactionMessage = serde_json::from_str(&params.message.clone()).unwrap();
+pub struct CancelTransaction;
 
-    let client = reqwest::Client::new();
-    let resp: reqwest::Response = client
-        .post(&_url.to_owned())
-        .header("usersignature", &params.usersignature.clone().unwrap())
-        .header("authsignature", &params.authsignature)
-        .json(&h)
-        .send()
-        .await?;
+impl SilaEndpoint for CancelTransaction {
+    type Message = CancelTransactionMessage;
+    type Response = CancelTransactionResponse;
 
-    let response_text = resp.text().await?;
-    let response: Result<CancelTransactionResponse, serde_json::Error> =
-        serde_json::from_str(&response_text);
+    const PATH: &'static str = "cancel_transaction";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
 
-    match response {
-        Ok(x) if x.status == Status::FAILURE => {
-            error!("cancel_transaction failure: {}", response_text);
-            Ok(x)
-        }
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!(
-                "JSON decoding failure in cancel_transaction response: {}",
-                response_text
-            );
-            Err(Box::from(e))
-        }
+    fn is_failure(response: &CancelTransactionResponse) -> bool {
+        response.status == Status::FAILURE
     }
 }
+
+pub async fn cancel_transaction(
+    params: &SignedMessageParams,
+) -> Result<CancelTransactionResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<CancelTransaction>(params).await
+}
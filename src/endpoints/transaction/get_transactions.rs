@@ -2,10 +2,16 @@ use crate::header_message;
 use crate::Header;
 use crate::HeaderMessage;
 use crate::IssueProcessingType;
-use log::error;
+use futures::{stream, Stream, TryStreamExt};
 use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
-use crate::{SignedMessageParams, Status};
+use crate::{
+    execute, paginate, Paginated, RequiredSignatures, SignedMessageParams, Signatures, SilaAmount,
+    SilaEndpoint, Status,
+};
 
 #[derive(Deserialize, Serialize, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -13,6 +19,9 @@ pub enum TransactionType {
     Issue,
     Redeem,
     Transfer,
+    // types added by Sila after this crate was written
+    #[serde(other)]
+    Unknown,
 }
 
 impl std::fmt::Display for TransactionType {
@@ -21,6 +30,7 @@ impl std::fmt::Display for TransactionType {
             TransactionType::Issue => write!(f, "issue"),
             TransactionType::Redeem => write!(f, "redeem"),
             TransactionType::Transfer => write!(f, "transfer"),
+            TransactionType::Unknown => write!(f, "unknown"),
         }
     }
 }
@@ -36,6 +46,9 @@ pub enum TransactionStatus {
     Success,
     Rollback,
     Review,
+    // so one new status does not fail a whole page of transactions
+    #[serde(other)]
+    Unknown,
 }
 
 impl std::fmt::Display for TransactionStatus {
@@ -49,10 +62,63 @@ impl std::fmt::Display for TransactionStatus {
             TransactionStatus::Success => write!(f, "success"),
             TransactionStatus::Rollback => write!(f, "rollback"),
             TransactionStatus::Review => write!(f, "review"),
+            TransactionStatus::Unknown => write!(f, "unknown"),
         }
     }
 }
 
+impl FromStr for TransactionStatus {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match serde_json::from_value(serde_json::Value::String(s.to_string()))? {
+            TransactionStatus::Unknown => Err(serde::de::Error::custom(format!("unknown transaction status {}", s))),
+            x => Ok(x),
+        }
+    }
+}
+
+impl TransactionStatus {
+    // success is not strictly final (an ACH return can still reverse it), but it is the state
+    // callers wait for, so it is treated as terminal alongside the failure states
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TransactionStatus::Success
+                | TransactionStatus::Failed
+                | TransactionStatus::Reversed
+                | TransactionStatus::Rollback
+        )
+    }
+
+    pub fn can_transition_to(&self, next: &TransactionStatus) -> bool {
+        use TransactionStatus::*;
+
+        matches!(
+            (self, next),
+            (Queued, Pending)
+                | (Queued, Review)
+                | (Queued, Success)
+                | (Queued, Failed)
+                | (Review, Queued)
+                | (Review, Pending)
+                | (Review, Failed)
+                | (Pending, PendingConfirmation)
+                | (Pending, Review)
+                | (Pending, Success)
+                | (Pending, Failed)
+                | (PendingConfirmation, Success)
+                | (PendingConfirmation, Failed)
+                | (Success, Reversed)
+                | (Success, Rollback)
+                | (Failed, Rollback)
+                // a status added by Sila later can sit anywhere in the lifecycle
+                | (Unknown, _)
+                | (_, Unknown)
+        )
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub struct TransactionSearchFilters {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -64,9 +130,9 @@ pub struct TransactionSearchFilters {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sort_ascending: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub max_sila_amount: Option<i32>,
+    pub max_sila_amount: Option<SilaAmount>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub min_sila_amount: Option<i32>,
+    pub min_sila_amount: Option<SilaAmount>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub statuses: Option<Vec<TransactionStatus>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -156,11 +222,11 @@ impl From<GetTransactionsMessageParams> for GetTransactionsMessage {
         let sila_params = &*crate::SILA_PARAMS;
  
         let mut header_message: HeaderMessage = header_message();
-        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.user_handle = params.sila_handle.clone();
         header_message.header.auth_handle = sila_params.app_handle.clone();
 
-        if params.reference.is_some() {
-            header_message.header.reference = params.reference.unwrap();
+        if let Some(reference) = params.reference {
+            header_message.header.reference = reference;
         }
          
         GetTransactionsMessage {
@@ -180,6 +246,18 @@ pub struct TransactionTimelineItem {
     pub token_status: Option<String>,
 }
 
+impl TransactionTimelineItem {
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.date_epoch
+            .filter(|x| *x >= 0)
+            .map(|x| SystemTime::UNIX_EPOCH + Duration::from_secs(x as u64))
+    }
+
+    pub fn transaction_status(&self) -> Option<TransactionStatus> {
+        self.status.as_ref().and_then(|x| x.parse().ok())
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Transaction {
     pub user_handle: Option<String>,
@@ -187,7 +265,7 @@ pub struct Transaction {
     pub transaction_id: Option<String>,
     pub transaction_hash: Option<String>,
     pub transaction_type: Option<TransactionType>,
-    pub sila_amount: Option<i32>,
+    pub sila_amount: Option<SilaAmount>,
     pub status: Option<TransactionStatus>,
     pub usd_status: Option<String>,
     pub token_status: Option<String>,
@@ -260,59 +338,99 @@ impl std::fmt::Display for GetTransactionsResponse {
     }
 }
 
+impl Paginated for GetTransactionsResponse {
+    fn current_page(&self) -> Option<i32> {
+        self.pagination.as_ref().and_then(|x| x.current_page)
+    }
+
+    fn total_pages(&self) -> Option<i32> {
+        self.pagination.as_ref().and_then(|x| x.total_pages)
+    }
+}
+
+pub struct GetTransactions;
+
+impl SilaEndpoint for GetTransactions {
+    type Message = GetTransactionsMessage;
+    type Response = GetTransactionsResponse;
+
+    const PATH: &'static str = "get_transactions";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::OptionalUser;
+
+    fn is_failure(response: &GetTransactionsResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
 pub async fn get_transactions(
     params: &SignedMessageParams,
-) -> Result<GetTransactionsResponse, Box<dyn std::error::Error>> {
-    let sila_params = &*crate::SILA_PARAMS;
-    let _url: String = format!("{}/get_transactions", sila_params.gateway);
+) -> Result<GetTransactionsResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<GetTransactions>(params).await
+}
 
+// Every transaction across the pages of get_transactions, signing a fresh message per page.
+pub fn get_transactions_stream<F, Fut>(
+    params: GetTransactionsMessageParams,
+    sign: F,
+) -> impl Stream<Item = Result<Transaction, Box<dyn std::error::Error + Sync + Send>>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Signatures>,
+{
+    let first_page = params
+        .search_filters
+        .as_ref()
+        .and_then(|x| x.page)
+        .unwrap_or(1);
 
-    let h: GetTransactionsMessage = serde_json::from_str(&params.message.clone()).unwrap();
+    paginate::<GetTransactions, _, _>(first_page, move |page| {
+        let mut page_params = params.clone();
+        let mut filters = page_params.search_filters.take().unwrap_or_default();
+        filters.page = Option::from(page);
+        page_params.search_filters = Option::from(filters);
 
-    let client = reqwest::ClientBuilder::new()
-        .build()
-        .unwrap();
+        let sila_handle = params.sila_handle.clone();
+        let message = serde_json::to_string(&GetTransactionsMessage::from(page_params));
+        let signing = message.map(|x| (sign(x.clone()), x));
 
-    let resp: reqwest::Response;
-
-    match &params.usersignature {
-        Some(x) => {
-            resp = client
-                .post(&_url.to_owned())
-                .header("usersignature", x)
-                .header("authsignature", &params.authsignature)
-                .json(&h)
-                .send()
-                .await
-                .unwrap();
-        }
-        None => {
-            resp = client
-                .post(&_url.to_owned())
-                .header("authsignature", &params.authsignature)
-                .json(&h)
-                .send()
-                .await
-                .unwrap();
+        async move {
+            let (signing, message) = signing?;
+            let signatures = signing.await;
+
+            get_transactions(&SignedMessageParams {
+                sila_handle,
+                message,
+                usersignature: signatures.usersignature,
+                authsignature: signatures.authsignature,
+            })
+            .await
         }
-    }
+    })
+    .map_ok(|x| stream::iter(x.transactions.unwrap_or_default().into_iter().map(Ok)))
+    .try_flatten()
+}
 
-    let response_text = resp.text().await.unwrap();
-    let response: Result<GetTransactionsResponse, serde_json::Error> =
-        serde_json::from_str(&response_text);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    match response {
-        Ok(x) if x.status == Status::FAILURE => {
-            error!("get_transactions failure: {}", response_text);
-            Ok(x)
-        }
-        Ok(x) => Ok(x),
-        Err(_) => {
-            error!(
-                "JSON decoding failure in cancel_transaction response: {}",
-                response_text
-            );
-            Err(Box::from("JSON decoding failure"))
-        }
+    #[test]
+    fn decodes_types_statuses_and_fields_added_by_sila() {
+        let transaction: Transaction = serde_json::from_str(
+            r#"{
+                "transaction_id": "fee",
+                "transaction_type": "fee",
+                "sila_amount": 25,
+                "status": "settling",
+                "ledger_account_id": null,
+                "child_transactions": [],
+                "timeline": [{"date_epoch": 1665000400, "status": "settling", "status_code": 100}]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(transaction.transaction_type, Some(TransactionType::Unknown)));
+        assert!(transaction.status == Some(TransactionStatus::Unknown));
+        assert_eq!(transaction.timeline.unwrap().len(), 1);
     }
 }
@@ -1,50 +1,56 @@
-use crate::{header_message, Header, HeaderMessage, SignedMessageParams, Status};
+use crate::{
+    check_instant_ach, execute, header_message, CheckInstantAchMessage, Header, HeaderMessage,
+    InstantAchEligibility, RequiredSignatures, SignedMessageParams, SilaAmount, SilaEndpoint, Status,
+};
+use crate::endpoints::transaction::check_funding;
 use log::error;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all="SCREAMING_SNAKE_CASE")]
 pub enum IssueProcessingType {
     StandardAch,
     SameDayAch,
     InstantAch,
     InstantSettlement,
+    Card,
 }
 
-impl std::fmt::Display for IssueProcessingType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match *self {
-            IssueProcessingType::StandardAch => write!(f, "STANDARD_ACH"),
-            IssueProcessingType::SameDayAch => write!(f, "SAME_DAY_ACH"),
-            IssueProcessingType::InstantAch => write!(f, "INSTANCE_ACH"),
-            IssueProcessingType::InstantSettlement => write!(f, "INSTANT_SETTLEMENT"),
-        }
-    }
-}
+// the wire names, e.g. INSTANT_ACH
+serde_plain::derive_display_from_serialize!(IssueProcessingType);
 
 #[derive(Deserialize, Serialize)]
 pub struct IssueSilaMessage {
     pub header: Header,
-    pub amount: i32,
+    pub amount: SilaAmount,
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub account_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub card_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub descriptor: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub business_uuid: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub processing_type: Option<IssueProcessingType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination_id: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct IssueSilaMessageParams {
     pub sila_handle: String,
-    pub amount: i32,
+    pub amount: SilaAmount,
     pub account_name: Option<String>,
+    pub card_name: Option<String>,
     pub descriptor: Option<String>,
     pub business_uuid: Option<String>,
     pub processing_type: Option<IssueProcessingType>,
+    pub source_id: Option<String>,
+    pub destination_id: Option<String>,
     pub reference: Option<String>
 }
 
@@ -52,11 +58,14 @@ impl Default for IssueSilaMessageParams {
     fn default() -> Self {
         IssueSilaMessageParams {
             sila_handle: String::new(),
-            amount: 0,
-            account_name: Option::from("default".to_string()),
+            amount: SilaAmount::ZERO,
+            account_name: Option::None,
+            card_name: Option::None,
             descriptor: Option::None,
             business_uuid: Option::None,
             processing_type: Option::from(IssueProcessingType::StandardAch),
+            source_id: Option::None,
+            destination_id: Option::None,
             reference: Option::None,
         }
     }
@@ -70,8 +79,8 @@ impl From<IssueSilaMessageParams> for IssueSilaMessage {
         header_message.header.user_handle = Option::from(params.sila_handle.clone());
         header_message.header.auth_handle = sila_params.app_handle.clone();
 
-        if params.reference.is_some() {
-            header_message.header.reference = params.reference.unwrap();
+        if let Some(reference) = params.reference {
+            header_message.header.reference = reference;
         }
 
         IssueSilaMessage {
@@ -79,9 +88,12 @@ impl From<IssueSilaMessageParams> for IssueSilaMessage {
             message: "issue_msg".to_string(),
             amount: params.amount,
             account_name: params.account_name.clone(),
+            card_name: params.card_name.clone(),
             descriptor: params.descriptor.clone(),
             business_uuid: params.business_uuid.clone(),
             processing_type: params.processing_type.clone(),
+            source_id: params.source_id.clone(),
+            destination_id: params.destination_id.clone(),
         }
     }
 }
@@ -107,39 +119,158 @@ impl std::fmt::Display for IssueSilaResponse {
     }
 }
 
+pub struct IssueSila;
+
+impl SilaEndpoint for IssueSila {
+    type Message = IssueSilaMessage;
+    type Response = IssueSilaResponse;
+
+    const PATH: &'static str = "issue_sila";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn check(message: &IssueSilaMessage) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        if message.amount.is_zero() {
+            return Err(Box::from("issue_sila amount must be greater than zero"));
+        }
+
+        check_funding(
+            Self::PATH,
+            &message.account_name,
+            &message.card_name,
+            message.processing_type == Some(IssueProcessingType::Card),
+        )
+    }
+
+    fn is_failure(response: &IssueSilaResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
 pub async fn issue_sila(
     params: &SignedMessageParams,
-) -> Result<IssueSilaResponse, Box<dyn std::error::Error>> {
-    let sila_params = &*crate::SILA_PARAMS;
-    let _url: String = format!("{}/issue_sila", sila_params.gateway);
-
-    let h: IssueSilaMessage = serde_json::from_str(&params.message.clone()).unwrap();
-
-    let client = reqwest::ClientBuilder::new()
-        .build()
-        .unwrap();
-        
-    let resp: reqwest::Response = client
-        .post(&_url.to_owned())
-        .header("usersignature", &params.usersignature.clone().unwrap())
-        .header("authsignature", &params.authsignature)
-        .json(&h)
-        .send()
-        .await?;
-
-    let response_text = resp.text().await?;
-    let response: Result<IssueSilaResponse, serde_json::Error> =
-        serde_json::from_str(&response_text);
-
-    match response {
-        Ok(x) if x.status == Status::FAILURE => {
-            error!("issue_sila error: {}", response_text);
-            Ok(x)
+) -> Result<IssueSilaResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<IssueSila>(params).await
+}
+
+// Runs check_instant_ach before submitting an INSTANT_ACH issue and refuses to send the issue
+// when the account is not eligible. The check has to be for the same user and account as the
+// issue, or its result says nothing about it.
+pub async fn issue_sila_instant_ach(
+    check_params: &SignedMessageParams,
+    params: &SignedMessageParams,
+) -> Result<IssueSilaResponse, Box<dyn std::error::Error + Sync + Send>> {
+    let h: IssueSilaMessage = serde_json::from_str(&params.message.clone())?;
+
+    if h.processing_type != Some(IssueProcessingType::InstantAch) {
+        return Err(Box::from("issue_sila_instant_ach requires processing_type INSTANT_ACH"));
+    }
+
+    let c: CheckInstantAchMessage = serde_json::from_str(&check_params.message)?;
+    let account_name = h.account_name.as_deref().unwrap_or("default");
+
+    if c.header.user_handle != h.header.user_handle || c.account_name != account_name {
+        error!("issue_sila refused: check_instant_ach is for a different user or account");
+        return Err(Box::from(
+            "check_instant_ach must be for the same user_handle and account_name as the issue",
+        ));
+    }
+
+    let check = check_instant_ach(check_params).await?;
+    let eligibility = InstantAchEligibility::from(&check);
+
+    if !eligibility.eligible {
+        error!("issue_sila refused: {}", eligibility);
+        return Err(Box::from(format!(
+            "account is not eligible for INSTANT_ACH: {}",
+            eligibility.reasons.join("; ")
+        )));
+    }
+
+    issue_sila(params).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed<T: Serialize>(message: &T) -> SignedMessageParams {
+        SignedMessageParams {
+            sila_handle: Option::None,
+            message: serde_json::to_string(message).unwrap(),
+            usersignature: Option::None,
+            authsignature: String::new(),
         }
-        Ok(x) => Ok(x),
-        Err(_) => {
-            error!("issue_sila response error: {}", response_text);
-            Err(Box::from("issue_sila response error"))
+    }
+
+    #[test]
+    fn instant_ach_check_must_match_the_issue() {
+        let header = |handle: &str| Header {
+            user_handle: Option::from(handle.to_string()),
+            ..Default::default()
+        };
+
+        let issue = signed(&IssueSilaMessage {
+            header: header("user"),
+            amount: SilaAmount::from_sila(100).unwrap(),
+            message: "issue_msg".to_string(),
+            account_name: Option::None,
+            card_name: Option::None,
+            descriptor: Option::None,
+            business_uuid: Option::None,
+            processing_type: Option::from(IssueProcessingType::InstantAch),
+            source_id: Option::None,
+            destination_id: Option::None,
+        });
+
+        for (handle, account_name) in [("other", "default"), ("user", "savings")] {
+            let check = signed(&CheckInstantAchMessage {
+                header: header(handle),
+                account_name: account_name.to_string(),
+                kyc_level: Option::None,
+            });
+
+            let error = futures::executor::block_on(issue_sila_instant_ach(&check, &issue))
+                .err()
+                .unwrap();
+            assert!(error.to_string().contains("same user_handle and account_name"));
         }
     }
+
+    fn card_issue(card_name: Option<&str>) -> IssueSilaMessage {
+        IssueSilaMessage {
+            header: Header::default(),
+            amount: SilaAmount::from_sila(100).unwrap(),
+            message: "issue_msg".to_string(),
+            account_name: Option::None,
+            card_name: card_name.map(|x| x.to_string()),
+            descriptor: Option::None,
+            business_uuid: Option::None,
+            processing_type: Option::from(IssueProcessingType::Card),
+            source_id: Option::None,
+            destination_id: Option::None,
+        }
+    }
+
+    #[test]
+    fn card_issues_name_the_card_they_debit() {
+        let error = IssueSila::check(&card_issue(Option::None)).err().unwrap();
+        assert!(error.to_string().contains("requires card_name"));
+
+        let message = card_issue(Option::from("visa"));
+        assert!(IssueSila::check(&message).is_ok());
+
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(value["card_name"], "visa");
+        assert_eq!(value["processing_type"], "CARD");
+        assert!(value.get("account_name").is_none());
+
+        // an account issue leaves card_name off the wire
+        let message = IssueSilaMessage {
+            account_name: Option::from("default".to_string()),
+            processing_type: Option::None,
+            ..card_issue(Option::None)
+        };
+        assert!(IssueSila::check(&message).is_ok());
+        assert!(serde_json::to_value(&message).unwrap().get("card_name").is_none());
+    }
 }
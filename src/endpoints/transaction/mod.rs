@@ -2,4 +2,85 @@ pub mod issue_sila;
 pub mod redeem_sila;
 pub mod transfer_sila;
 pub mod cancel_transaction;
-pub mod get_transactions;
\ No newline at end of file
+pub mod get_transactions;
+pub mod approve_wire;
+
+// issue_sila and redeem_sila draw on either a linked account or a linked card, never both, and
+// a CARD processing type has to name the card.
+pub(crate) fn check_funding(
+    path: &str,
+    account_name: &Option<String>,
+    card_name: &Option<String>,
+    card_processing: bool,
+) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+    if account_name.is_some() && card_name.is_some() {
+        return Err(Box::from(format!("{} takes account_name or card_name, not both", path)));
+    }
+
+    if card_processing && card_name.is_none() {
+        return Err(Box::from(format!("{} with processing_type CARD requires card_name", path)));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IssueProcessingType, RedeemProcessingType};
+
+    // The matches stop compiling when a variant is added, so every processing type is covered.
+    #[test]
+    fn processing_types_serialize_as_they_display() {
+        let issue_name = |x: &IssueProcessingType| match x {
+            IssueProcessingType::StandardAch => "STANDARD_ACH",
+            IssueProcessingType::SameDayAch => "SAME_DAY_ACH",
+            IssueProcessingType::InstantAch => "INSTANT_ACH",
+            IssueProcessingType::InstantSettlement => "INSTANT_SETTLEMENT",
+            IssueProcessingType::Card => "CARD",
+        };
+
+        for x in [
+            IssueProcessingType::StandardAch,
+            IssueProcessingType::SameDayAch,
+            IssueProcessingType::InstantAch,
+            IssueProcessingType::InstantSettlement,
+            IssueProcessingType::Card,
+        ] {
+            assert_eq!(serde_json::to_string(&x).unwrap(), format!("\"{}\"", x));
+            assert_eq!(x.to_string(), issue_name(&x));
+            assert!(serde_json::from_str::<IssueProcessingType>(&format!("\"{}\"", x)).unwrap() == x);
+        }
+
+        let redeem_name = |x: &RedeemProcessingType| match x {
+            RedeemProcessingType::StandardAch => "STANDARD_ACH",
+            RedeemProcessingType::SameDayAch => "SAME_DAY_ACH",
+            RedeemProcessingType::Card => "CARD",
+            RedeemProcessingType::Wire => "WIRE",
+        };
+
+        for x in [
+            RedeemProcessingType::StandardAch,
+            RedeemProcessingType::SameDayAch,
+            RedeemProcessingType::Card,
+            RedeemProcessingType::Wire,
+        ] {
+            assert_eq!(serde_json::to_string(&x).unwrap(), format!("\"{}\"", x));
+            assert_eq!(x.to_string(), redeem_name(&x));
+            assert!(serde_json::from_str::<RedeemProcessingType>(&format!("\"{}\"", x)).unwrap() == x);
+        }
+    }
+
+    #[test]
+    fn funding_is_an_account_or_a_card() {
+        let name = Option::from("default".to_string());
+
+        assert!(check_funding("issue_sila", &name, &Option::None, false).is_ok());
+        assert!(check_funding("issue_sila", &Option::None, &name, true).is_ok());
+        assert!(check_funding("issue_sila", &Option::None, &Option::None, false).is_ok());
+
+        assert!(check_funding("issue_sila", &name, &name, true).is_err());
+        assert!(check_funding("issue_sila", &name, &Option::None, true).is_err());
+        assert!(check_funding("issue_sila", &Option::None, &Option::None, true).is_err());
+    }
+}
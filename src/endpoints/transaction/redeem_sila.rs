@@ -1,48 +1,57 @@
-use crate::Header;
-use log::error;
+use crate::endpoints::transaction::check_funding;
+use crate::{execute, Header, RequiredSignatures, SilaEndpoint};
 use serde::{Deserialize, Serialize};
 
-use crate::{header_message, HeaderMessage, SignedMessageParams, Status};
+use crate::{header_message, HeaderMessage, SignedMessageParams, SilaAmount, Status};
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all="SCREAMING_SNAKE_CASE")]
 pub enum RedeemProcessingType {
     StandardAch,
     SameDayAch,
+    Card,
+    Wire,
 }
 
-impl std::fmt::Display for RedeemProcessingType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match *self {
-            RedeemProcessingType::StandardAch => write!(f, "STANDARD_ACH"),
-            RedeemProcessingType::SameDayAch => write!(f, "SAME_DAY_ACH"),
-        }
-    }
-}
+// the wire names, e.g. SAME_DAY_ACH
+serde_plain::derive_display_from_serialize!(RedeemProcessingType);
 
 #[derive(Deserialize, Serialize)]
 pub struct RedeemSilaMessage {
     pub header: Header,
-    pub amount: i32,
+    pub amount: SilaAmount,
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub account_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub card_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub descriptor: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub business_uuid: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub processing_type: Option<RedeemProcessingType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination_id: Option<String>,
+    // sandbox only: scripts the outcome of a WIRE redeem; the wire itself goes to the linked account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mock_wire_account_name: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct RedeemSilaMessageParams {
     pub sila_handle: String,
-    pub amount: i32,
+    pub amount: SilaAmount,
     pub account_name: Option<String>,
+    pub card_name: Option<String>,
     pub descriptor: Option<String>,
     pub business_uuid: Option<String>,
     pub processing_type: Option<RedeemProcessingType>,
+    pub source_id: Option<String>,
+    pub destination_id: Option<String>,
+    pub mock_wire_account_name: Option<String>,
     pub reference: Option<String>,
 }
 
@@ -50,11 +59,15 @@ impl Default for RedeemSilaMessageParams {
     fn default() -> Self {
         RedeemSilaMessageParams {
             sila_handle: String::new(),
-            amount: 0,
-            account_name: Option::from("default".to_string()),
+            amount: SilaAmount::ZERO,
+            account_name: Option::None,
+            card_name: Option::None,
             descriptor: Option::None,
             business_uuid: Option::None,
             processing_type: Option::from(RedeemProcessingType::StandardAch),
+            source_id: Option::None,
+            destination_id: Option::None,
+            mock_wire_account_name: Option::None,
             reference: Option::None
         }
     }
@@ -68,8 +81,8 @@ impl From<RedeemSilaMessageParams> for RedeemSilaMessage {
         header_message.header.user_handle = Option::from(params.sila_handle.clone());
         header_message.header.auth_handle = sila_params.app_handle.clone();
 
-        if params.reference.is_some() {
-            header_message.header.reference = params.reference.unwrap();
+        if let Some(reference) = params.reference {
+            header_message.header.reference = reference;
         }
 
         RedeemSilaMessage {
@@ -77,9 +90,13 @@ impl From<RedeemSilaMessageParams> for RedeemSilaMessage {
             message: "redeem_msg".to_string(),
             amount: params.amount,
             account_name: params.account_name.clone(),
+            card_name: params.card_name.clone(),
             descriptor: params.descriptor.clone(),
             business_uuid: params.business_uuid.clone(),
             processing_type: params.processing_type.clone(),
+            source_id: params.source_id.clone(),
+            destination_id: params.destination_id.clone(),
+            mock_wire_account_name: params.mock_wire_account_name.clone(),
         }
     }
 }
@@ -105,36 +122,35 @@ impl std::fmt::Display for RedeemSilaResponse {
     }
 }
 
+pub struct RedeemSila;
+
+impl SilaEndpoint for RedeemSila {
+    type Message = RedeemSilaMessage;
+    type Response = RedeemSilaResponse;
+
+    const PATH: &'static str = "redeem_sila";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn check(message: &RedeemSilaMessage) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        if message.amount.is_zero() {
+            return Err(Box::from("redeem_sila amount must be greater than zero"));
+        }
+
+        check_funding(
+            Self::PATH,
+            &message.account_name,
+            &message.card_name,
+            message.processing_type == Some(RedeemProcessingType::Card),
+        )
+    }
+
+    fn is_failure(response: &RedeemSilaResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
 pub async fn redeem_sila(
     params: &SignedMessageParams,
 ) -> Result<RedeemSilaResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
-    let _url: String = format!("{}/redeem_sila", sila_params.gateway);
-
-    let h: RedeemSilaMessage = serde_json::from_str(&params.message.clone()).unwrap();
-
-    let client = reqwest::Client::new();
-    let resp: reqwest::Response = client
-        .post(&_url.to_owned())
-        .header("usersignature", &params.usersignature.clone().unwrap())
-        .header("authsignature", &params.authsignature)
-        .json(&h)
-        .send()
-        .await?;
-
-    let response_text = resp.text().await?;
-    let response: Result<RedeemSilaResponse, serde_json::Error> =
-        serde_json::from_str(&response_text);
-
-    match response {
-        Ok(x) if x.status == Status::FAILURE => {
-            error!("redeem_sila error: {}", response_text);
-            Ok(x)
-        }
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("redeem_sila response error: {}", response_text);
-            Err(Box::from(e))
-        }
-    }
+    execute::<RedeemSila>(params).await
 }
@@ -1,12 +1,14 @@
-use log::error;
 use serde::{Deserialize, Serialize};
 
-use crate::{header_message, Header, HeaderMessage, SignedMessageParams, Status};
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaAmount, SilaEndpoint, Status,
+};
 
 #[derive(Deserialize, Serialize)]
 pub struct TransferSilaMessage {
     pub header: Header,
-    pub amount: i32,
+    pub amount: SilaAmount,
     pub message: String,
     pub destination_handle: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -21,10 +23,10 @@ pub struct TransferSilaMessage {
     pub destination_id: Option<String>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct TransferSilaMessageParams {
     pub sila_handle: String,
-    pub amount: i32,
+    pub amount: SilaAmount,
     pub descriptor: Option<String>,
     pub destination_handle: String,
     pub destination_address: Option<String>,
@@ -34,21 +36,6 @@ pub struct TransferSilaMessageParams {
     pub reference: Option<String>,
 }
 
-impl Default for TransferSilaMessageParams {
-    fn default() -> Self {
-        TransferSilaMessageParams {
-            sila_handle: String::new(),
-            amount: 0,
-            descriptor: Option::None,
-            destination_handle: String::new(),
-            destination_address: Option::None,
-            destination_wallet: Option::None,
-            destination_id: Option::None,
-            source_id: Option::None,
-            reference: Option::None,
-        }
-    }
-}
 
 impl From<TransferSilaMessageParams> for TransferSilaMessage {
     fn from(params: TransferSilaMessageParams) -> Self {
@@ -58,8 +45,8 @@ impl From<TransferSilaMessageParams> for TransferSilaMessage {
         header_message.header.user_handle = Option::from(params.sila_handle.clone());
         header_message.header.auth_handle = sila_params.app_handle.clone();
 
-        if params.reference.is_some() {
-            header_message.header.reference = params.reference.unwrap();
+        if let Some(reference) = params.reference {
+            header_message.header.reference = reference;
         }
 
         TransferSilaMessage {
@@ -97,36 +84,30 @@ impl std::fmt::Display for TransferSilaResponse {
     }
 }
 
+pub struct TransferSila;
+
+impl SilaEndpoint for TransferSila {
+    type Message = TransferSilaMessage;
+    type Response = TransferSilaResponse;
+
+    const PATH: &'static str = "transfer_sila";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn check(message: &TransferSilaMessage) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        if message.amount.is_zero() {
+            return Err(Box::from("transfer_sila amount must be greater than zero"));
+        }
+
+        Ok(())
+    }
+
+    fn is_failure(response: &TransferSilaResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
 pub async fn transfer_sila(
     params: &SignedMessageParams,
 ) -> Result<TransferSilaResponse, Box<dyn std::error::Error + Sync + Send>> {
-    let sila_params = &*crate::SILA_PARAMS;
-    let _url: String = format!("{}/transfer_sila", sila_params.gateway);
-
-    let h: TransferSilaMessage = serde_json::from_str(&params.message.clone()).unwrap();
-
-    let client = reqwest::Client::new();
-    let resp: reqwest::Response = client
-        .post(&_url.to_owned())
-        .header("usersignature", &params.usersignature.clone().unwrap())
-        .header("authsignature", &params.authsignature)
-        .json(&h)
-        .send()
-        .await?;
-
-    let response_text = resp.text().await?;
-    let response: Result<TransferSilaResponse, serde_json::Error> =
-        serde_json::from_str(&response_text);
-
-    match response {
-        Ok(x) if x.status == Status::FAILURE => {
-            error!("transfer_sila error: {}", response_text);
-            Ok(x)
-        }
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("transfer_sila response error: {}", response_text);
-            Err(Box::from(e))
-        }
-    }
+    execute::<TransferSila>(params).await
 }
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::virtual_account::*;
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct CloseVirtualAccountMessage {
+    pub header: Header,
+    pub virtual_account_id: String,
+    pub account_number: String,
+}
+
+#[derive(Clone)]
+pub struct CloseVirtualAccountMessageParams {
+    pub sila_handle: String,
+    pub virtual_account_id: String,
+    pub account_number: String,
+}
+
+impl From<CloseVirtualAccountMessageParams> for CloseVirtualAccountMessage {
+    fn from(params: CloseVirtualAccountMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        CloseVirtualAccountMessage {
+            header: header_message.header,
+            virtual_account_id: params.virtual_account_id.clone(),
+            account_number: params.account_number.clone(),
+        }
+    }
+}
+
+pub struct CloseVirtualAccount;
+
+impl SilaEndpoint for CloseVirtualAccount {
+    type Message = CloseVirtualAccountMessage;
+    type Response = VirtualAccountResponse;
+
+    const PATH: &'static str = "close_virtual_account";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &VirtualAccountResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn close_virtual_account(
+    params: &SignedMessageParams,
+) -> Result<VirtualAccountResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<CloseVirtualAccount>(params).await
+}
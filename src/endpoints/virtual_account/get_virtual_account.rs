@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::virtual_account::*;
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct GetVirtualAccountMessage {
+    pub header: Header,
+    pub virtual_account_id: String,
+}
+
+#[derive(Clone)]
+pub struct GetVirtualAccountMessageParams {
+    pub sila_handle: String,
+    pub virtual_account_id: String,
+}
+
+impl From<GetVirtualAccountMessageParams> for GetVirtualAccountMessage {
+    fn from(params: GetVirtualAccountMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        GetVirtualAccountMessage {
+            header: header_message.header,
+            virtual_account_id: params.virtual_account_id.clone(),
+        }
+    }
+}
+
+pub struct GetVirtualAccount;
+
+impl SilaEndpoint for GetVirtualAccount {
+    type Message = GetVirtualAccountMessage;
+    type Response = VirtualAccountResponse;
+
+    const PATH: &'static str = "get_virtual_account";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &VirtualAccountResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn get_virtual_account(
+    params: &SignedMessageParams,
+) -> Result<VirtualAccountResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<GetVirtualAccount>(params).await
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::virtual_account::*;
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct GetVirtualAccountsMessage {
+    pub header: Header,
+}
+
+#[derive(Clone)]
+pub struct GetVirtualAccountsMessageParams {
+    pub sila_handle: String,
+}
+
+impl From<GetVirtualAccountsMessageParams> for GetVirtualAccountsMessage {
+    fn from(params: GetVirtualAccountsMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        GetVirtualAccountsMessage {
+            header: header_message.header,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct GetVirtualAccountsResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub virtual_accounts: Option<Vec<VirtualAccount>>,
+}
+
+pub struct GetVirtualAccounts;
+
+impl SilaEndpoint for GetVirtualAccounts {
+    type Message = GetVirtualAccountsMessage;
+    type Response = GetVirtualAccountsResponse;
+
+    const PATH: &'static str = "get_virtual_accounts";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &GetVirtualAccountsResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn get_virtual_accounts(
+    params: &SignedMessageParams,
+) -> Result<GetVirtualAccountsResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<GetVirtualAccounts>(params).await
+}
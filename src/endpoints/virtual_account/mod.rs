@@ -0,0 +1,105 @@
+pub mod close_virtual_account;
+pub mod get_virtual_account;
+pub mod get_virtual_accounts;
+pub mod open_virtual_account;
+pub mod update_virtual_account;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Status;
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct VirtualAccount {
+    pub virtual_account_id: Option<String>,
+    pub virtual_account_name: Option<String>,
+    pub account_number: Option<String>,
+    pub routing_number: Option<String>,
+    pub account_type: Option<String>,
+    pub active: Option<bool>,
+    pub closed: Option<bool>,
+    pub ach_debit_enabled: Option<bool>,
+    pub ach_credit_enabled: Option<bool>,
+    pub statements_enabled: Option<bool>,
+    pub created_epoch: Option<i64>,
+    pub closed_epoch: Option<i64>,
+}
+
+impl std::fmt::Display for VirtualAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "VirtualAccount(virtual_account_id: {}, virtual_account_name: {}, routing_number: {}, active: {})",
+            self.virtual_account_id.as_ref().unwrap_or(&"none".to_string()),
+            self.virtual_account_name.as_ref().unwrap_or(&"none".to_string()),
+            self.routing_number.as_ref().unwrap_or(&"none".to_string()),
+            self.active.unwrap_or(false)
+        )
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct VirtualAccountResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub virtual_account: Option<VirtualAccount>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_a_virtual_account_response() {
+        let response: VirtualAccountResponse = serde_json::from_value(serde_json::json!({
+            "success": true,
+            "status": "SUCCESS",
+            "message": "Virtual account opened.",
+            "reference": "ref",
+            "response_time_ms": "120",
+            "virtual_account": {
+                "virtual_account_id": "va-1",
+                "virtual_account_name": "default",
+                "account_number": "0123456789",
+                "routing_number": "123456780",
+                "account_type": "VIRTUAL_ACCOUNT",
+                "active": true,
+                "closed": false,
+                "ach_debit_enabled": true,
+                "ach_credit_enabled": false,
+                "statements_enabled": true,
+                "created_epoch": 1634000000,
+                "closed_epoch": null
+            }
+        }))
+        .unwrap();
+
+        assert!(response.success);
+        assert!(response.status == Status::SUCCESS);
+
+        let account = response.virtual_account.unwrap();
+        assert_eq!(account.virtual_account_id.as_deref(), Some("va-1"));
+        assert_eq!(account.account_number.as_deref(), Some("0123456789"));
+        assert_eq!(account.active, Some(true));
+        assert_eq!(account.closed, Some(false));
+        assert_eq!(account.ach_debit_enabled, Some(true));
+        assert_eq!(account.ach_credit_enabled, Some(false));
+        assert_eq!(account.created_epoch, Some(1634000000));
+        assert_eq!(account.closed_epoch, None);
+        assert_eq!(
+            account.to_string(),
+            "VirtualAccount(virtual_account_id: va-1, virtual_account_name: default, routing_number: 123456780, active: true)"
+        );
+
+        // a failed open carries no account
+        let response: VirtualAccountResponse = serde_json::from_value(serde_json::json!({
+            "success": false,
+            "status": "FAILURE",
+            "message": "user has not passed KYC"
+        }))
+        .unwrap();
+
+        assert!(response.status == Status::FAILURE);
+        assert!(response.virtual_account.is_none());
+    }
+}
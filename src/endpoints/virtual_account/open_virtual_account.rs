@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::virtual_account::*;
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct OpenVirtualAccountMessage {
+    pub header: Header,
+    pub virtual_account_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ach_debit_enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ach_credit_enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statements_enabled: Option<bool>,
+}
+
+#[derive(Clone)]
+pub struct OpenVirtualAccountMessageParams {
+    pub sila_handle: String,
+    pub virtual_account_name: String,
+    pub ach_debit_enabled: Option<bool>,
+    pub ach_credit_enabled: Option<bool>,
+    pub statements_enabled: Option<bool>,
+    pub reference: Option<String>,
+}
+
+impl Default for OpenVirtualAccountMessageParams {
+    fn default() -> Self {
+        OpenVirtualAccountMessageParams {
+            sila_handle: String::new(),
+            virtual_account_name: "default".to_string(),
+            ach_debit_enabled: Option::None,
+            ach_credit_enabled: Option::None,
+            statements_enabled: Option::None,
+            reference: Option::None,
+        }
+    }
+}
+
+impl From<OpenVirtualAccountMessageParams> for OpenVirtualAccountMessage {
+    fn from(params: OpenVirtualAccountMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        if let Some(reference) = params.reference {
+            header_message.header.reference = reference;
+        }
+
+        OpenVirtualAccountMessage {
+            header: header_message.header,
+            virtual_account_name: params.virtual_account_name.clone(),
+            ach_debit_enabled: params.ach_debit_enabled,
+            ach_credit_enabled: params.ach_credit_enabled,
+            statements_enabled: params.statements_enabled,
+        }
+    }
+}
+
+pub struct OpenVirtualAccount;
+
+impl SilaEndpoint for OpenVirtualAccount {
+    type Message = OpenVirtualAccountMessage;
+    type Response = VirtualAccountResponse;
+
+    const PATH: &'static str = "open_virtual_account";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &VirtualAccountResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn open_virtual_account(
+    params: &SignedMessageParams,
+) -> Result<VirtualAccountResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<OpenVirtualAccount>(params).await
+}
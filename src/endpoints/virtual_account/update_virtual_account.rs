@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::virtual_account::*;
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct UpdateVirtualAccountMessage {
+    pub header: Header,
+    pub virtual_account_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub virtual_account_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ach_debit_enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ach_credit_enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statements_enabled: Option<bool>,
+}
+
+#[derive(Clone, Default)]
+pub struct UpdateVirtualAccountMessageParams {
+    pub sila_handle: String,
+    pub virtual_account_id: String,
+    pub virtual_account_name: Option<String>,
+    pub active: Option<bool>,
+    pub ach_debit_enabled: Option<bool>,
+    pub ach_credit_enabled: Option<bool>,
+    pub statements_enabled: Option<bool>,
+}
+
+impl From<UpdateVirtualAccountMessageParams> for UpdateVirtualAccountMessage {
+    fn from(params: UpdateVirtualAccountMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = Option::from(params.sila_handle.clone());
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        UpdateVirtualAccountMessage {
+            header: header_message.header,
+            virtual_account_id: params.virtual_account_id.clone(),
+            virtual_account_name: params.virtual_account_name.clone(),
+            active: params.active,
+            ach_debit_enabled: params.ach_debit_enabled,
+            ach_credit_enabled: params.ach_credit_enabled,
+            statements_enabled: params.statements_enabled,
+        }
+    }
+}
+
+pub struct UpdateVirtualAccount;
+
+impl SilaEndpoint for UpdateVirtualAccount {
+    type Message = UpdateVirtualAccountMessage;
+    type Response = VirtualAccountResponse;
+
+    const PATH: &'static str = "update_virtual_account";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::User;
+
+    fn is_failure(response: &VirtualAccountResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn update_virtual_account(
+    params: &SignedMessageParams,
+) -> Result<VirtualAccountResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<UpdateVirtualAccount>(params).await
+}
@@ -1,45 +1,255 @@
+use log::{error, warn};
 use serde::{Deserialize, Serialize};
-use log::error;
+use std::time::Duration;
+use web3::types::{Bytes, CallRequest, H160, U256};
 
-#[derive(Serialize)]
-pub struct RequestSilaBalanceParams {
-    pub blockchain_address: String
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaAmount, SilaEndpoint,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct GetSilaBalanceMessage {
+    pub header: Header,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+// Either an address, a handle, or both; with only a handle Sila reports the balance of the
+// entity's registered wallet.
+#[derive(Clone, Default)]
+pub struct GetSilaBalanceMessageParams {
+    pub sila_handle: Option<String>,
+    pub address: Option<H160>,
+}
+
+impl From<GetSilaBalanceMessageParams> for GetSilaBalanceMessage {
+    fn from(params: GetSilaBalanceMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = params.sila_handle.clone();
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        GetSilaBalanceMessage {
+            header: header_message.header,
+            address: params.address.map(|x| format!("{:#x}", x)),
+        }
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct SilaBalanceResponse {
-    pub success: bool,
-    pub status: String,
-    pub response_time_ms: String,
-    pub address: String,
-    pub sila_balance: i32,
-    pub reference: String
-}
-
-pub async fn get_sila_balance(params: &RequestSilaBalanceParams) -> Result<SilaBalanceResponse, Box<dyn std::error::Error>> {
-    let sila_params = &*crate::SILA_PARAMS;
-
-    let _url: String = format!("{}/get_sila_balance", sila_params.gateway);
-
-    let client = reqwest::Client::new();
-    let resp = client
-        .post(&_url.to_owned())
-        .json(&params)
-        .send()
-        .await?;
-    
-    let response_text = resp.text().await?;
-    let response : Result<SilaBalanceResponse, serde_json::Error> = serde_json::from_str(&response_text);
-
-    match response {
-        Ok(x) if x.success != true => {
-            error!("get_sila_balance error: {}", response_text);
-            Ok(x)
-        },
-        Ok(x) => Ok(x),
-        Err(e) => {
-            error!("get_sila_balance response error: {}", response_text);
-            Err(Box::from(e))
+    pub success: Option<bool>,
+    pub status: Option<String>,
+    pub message: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub address: Option<String>,
+    pub sila_balance: Option<SilaAmount>,
+    pub reference: Option<String>,
+}
+
+impl SilaBalanceResponse {
+    pub fn is_success(&self) -> bool {
+        self.success.unwrap_or(false) || self.status.as_deref() == Some("SUCCESS")
+    }
+
+    pub fn balance(&self) -> Option<SilaAmount> {
+        self.sila_balance.filter(|_| self.is_success())
+    }
+}
+
+pub struct GetSilaBalance;
+
+impl SilaEndpoint for GetSilaBalance {
+    type Message = GetSilaBalanceMessage;
+    type Response = SilaBalanceResponse;
+
+    const PATH: &'static str = "get_sila_balance";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::OptionalUser;
+
+    fn check(message: &GetSilaBalanceMessage) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        let has_handle = message.header.user_handle.as_deref().is_some_and(|x| !x.is_empty());
+
+        if message.address.is_none() && !has_handle {
+            return Err(Box::from("get_sila_balance requires an address or a sila_handle"));
         }
+
+        Ok(())
+    }
+
+    fn is_failure(response: &SilaBalanceResponse) -> bool {
+        !response.is_success()
     }
-}
\ No newline at end of file
+}
+
+pub async fn get_sila_balance(
+    params: &SignedMessageParams,
+) -> Result<SilaBalanceResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<GetSilaBalance>(params).await
+}
+
+// The SILA ERC-20 contract and the JSON-RPC node to read it through. decimals is the token's
+// on-chain precision; balances are truncated to whole SILA. The timeout is carried here rather
+// than read from SILA_PARAMS so chain-only use needs no Sila app configuration.
+#[derive(Clone)]
+pub struct OnChainBalanceParams {
+    pub rpc_url: String,
+    pub token_address: H160,
+    pub decimals: u32,
+    pub timeout: Duration,
+}
+
+// keccak256("balanceOf(address)")[..4]
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+
+pub async fn get_onchain_sila_balance(
+    params: &OnChainBalanceParams,
+    address: H160,
+) -> Result<SilaAmount, Box<dyn std::error::Error + Sync + Send>> {
+    // 10^78 no longer fits in a U256
+    if params.decimals > 77 {
+        return Err(Box::from(format!("token decimals {} is out of range", params.decimals)));
+    }
+
+    let transport = web3::transports::Http::new(&params.rpc_url)?;
+    let web3 = web3::Web3::new(transport);
+
+    let mut data = BALANCE_OF_SELECTOR.to_vec();
+    data.extend_from_slice(&[0u8; 12]);
+    data.extend_from_slice(address.as_bytes());
+
+    let call = web3.eth().call(
+        CallRequest {
+            to: Option::from(params.token_address),
+            data: Option::from(Bytes(data)),
+            ..Default::default()
+        },
+        Option::None,
+    );
+
+    let result = tokio::time::timeout(params.timeout, call)
+        .await
+        .map_err(|_| format!("balanceOf call to {} timed out", params.rpc_url))??;
+
+    if result.0.len() != 32 {
+        error!("unexpected balanceOf result: {}", hex::encode(&result.0));
+        return Err(Box::from("unexpected balanceOf result length"));
+    }
+
+    whole_sila(U256::from_big_endian(&result.0), params.decimals, address)
+}
+
+fn whole_sila(
+    raw: U256,
+    decimals: u32,
+    address: H160,
+) -> Result<SilaAmount, Box<dyn std::error::Error + Sync + Send>> {
+    let (whole, remainder) = raw.div_mod(U256::exp10(decimals as usize));
+
+    if !remainder.is_zero() {
+        warn!("on-chain balance of {:#x} has a fractional SILA part, truncating", address);
+    }
+
+    // amounts are built from i64, so bound the balance there rather than let the cast wrap
+    if whole > U256::from(i64::MAX) {
+        return Err(Box::from("on-chain SILA balance is out of range"));
+    }
+
+    Ok(SilaAmount::from_sila(whole.as_u64() as i64)?)
+}
+
+pub struct BalanceCrossCheck {
+    pub address: H160,
+    pub api_balance: Option<SilaAmount>,
+    pub chain_balance: SilaAmount,
+}
+
+impl BalanceCrossCheck {
+    pub fn matches(&self) -> bool {
+        self.api_balance == Some(self.chain_balance)
+    }
+}
+
+impl std::fmt::Display for BalanceCrossCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BalanceCrossCheck(address: {:#x}, api_balance: {}, chain_balance: {})",
+            self.address,
+            self.api_balance.map(|x| x.to_string()).unwrap_or_else(|| "none".to_string()),
+            self.chain_balance
+        )
+    }
+}
+
+pub async fn cross_check_sila_balance(
+    params: &SignedMessageParams,
+    chain: &OnChainBalanceParams,
+    address: H160,
+) -> Result<BalanceCrossCheck, Box<dyn std::error::Error + Sync + Send>> {
+    let api = get_sila_balance(params).await?;
+    let chain_balance = get_onchain_sila_balance(chain, address).await?;
+
+    let check = BalanceCrossCheck {
+        address,
+        api_balance: api.balance(),
+        chain_balance,
+    };
+
+    if !check.matches() {
+        warn!("SILA balance mismatch: {}", check);
+    }
+
+    Ok(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_balances_are_out_of_range_rather_than_negative() {
+        let scale = U256::exp10(18);
+
+        let max = U256::from(i64::MAX) * scale + U256::from(1);
+        let balance = whole_sila(max, 18, H160::zero()).unwrap();
+        assert!(balance == SilaAmount::from_sila(i64::MAX).unwrap());
+
+        let over = U256::from(i64::MAX as u64 + 1) * scale;
+        let error = whole_sila(over, 18, H160::zero()).err().unwrap();
+        assert!(error.to_string().contains("out of range"));
+    }
+
+    #[test]
+    fn refuses_a_message_with_neither_address_nor_handle() {
+        let message = |user_handle: Option<&str>, address: Option<&str>| GetSilaBalanceMessage {
+            header: Header {
+                user_handle: user_handle.map(|x| x.to_string()),
+                ..Default::default()
+            },
+            address: address.map(|x| x.to_string()),
+        };
+
+        let error = GetSilaBalance::check(&message(None, None)).err().unwrap();
+        assert!(error.to_string().contains("requires an address or a sila_handle"));
+        assert!(GetSilaBalance::check(&message(Some(""), None)).is_err());
+
+        assert!(GetSilaBalance::check(&message(Some("user"), None)).is_ok());
+        assert!(GetSilaBalance::check(&message(None, Some("0x65a796a4bd3aaf6370791beffb1a86eacfdbc3c1"))).is_ok());
+    }
+
+    #[test]
+    fn refuses_decimals_beyond_u256() {
+        let params = OnChainBalanceParams {
+            rpc_url: "http://127.0.0.1:1".to_string(),
+            token_address: H160::zero(),
+            decimals: 78,
+            timeout: Duration::from_secs(1),
+        };
+
+        let error = futures::executor::block_on(get_onchain_sila_balance(&params, H160::zero()))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("decimals 78"));
+        assert!(whole_sila(U256::MAX, 77, H160::zero()).is_ok());
+    }
+}
@@ -0,0 +1,201 @@
+use futures::{stream, Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+use crate::endpoints::webhook::*;
+use crate::{
+    header_message, retry_webhook, Header, HeaderMessage, RetryWebhookMessage,
+    RetryWebhookMessageParams, RetryWebhookResponse, SignedMessageParams, Signatures, Status,
+};
+use crate::{execute, paginate, Paginated, RequiredSignatures, SilaEndpoint};
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct WebhookSearchFilters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivered: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_ascending: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_handle: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_epoch: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_epoch: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<i32>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct GetWebhooksMessage {
+    pub header: Header,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_filters: Option<WebhookSearchFilters>,
+}
+
+#[derive(Clone, Default)]
+pub struct GetWebhooksMessageParams {
+    pub sila_handle: Option<String>,
+    pub search_filters: Option<WebhookSearchFilters>,
+}
+
+impl From<GetWebhooksMessageParams> for GetWebhooksMessage {
+    fn from(params: GetWebhooksMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.user_handle = params.sila_handle.clone();
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        GetWebhooksMessage {
+            header: header_message.header,
+            message: "header_msg".to_string(),
+            search_filters: params.search_filters.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WebhookPagination {
+    pub returned_count: Option<i32>,
+    pub total_count: Option<i32>,
+    pub current_page: Option<i32>,
+    pub total_pages: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetWebhooksResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+    pub webhooks: Option<Vec<Webhook>>,
+    pub pagination: Option<WebhookPagination>,
+}
+
+impl Paginated for GetWebhooksResponse {
+    fn current_page(&self) -> Option<i32> {
+        self.pagination.as_ref().and_then(|x| x.current_page)
+    }
+
+    fn total_pages(&self) -> Option<i32> {
+        self.pagination.as_ref().and_then(|x| x.total_pages)
+    }
+}
+
+pub struct GetWebhooks;
+
+impl SilaEndpoint for GetWebhooks {
+    type Message = GetWebhooksMessage;
+    type Response = GetWebhooksResponse;
+
+    const PATH: &'static str = "get_webhooks";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::App;
+
+    fn is_failure(response: &GetWebhooksResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn get_webhooks(
+    params: &SignedMessageParams,
+) -> Result<GetWebhooksResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<GetWebhooks>(params).await
+}
+
+pub fn get_webhooks_stream<F, Fut>(
+    params: GetWebhooksMessageParams,
+    sign: F,
+) -> impl Stream<Item = Result<Webhook, Box<dyn std::error::Error + Sync + Send>>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Signatures>,
+{
+    let first_page = params
+        .search_filters
+        .as_ref()
+        .and_then(|x| x.page)
+        .unwrap_or(1);
+
+    paginate::<GetWebhooks, _, _>(first_page, move |page| {
+        let mut page_params = params.clone();
+        let mut filters = page_params.search_filters.take().unwrap_or_default();
+        filters.page = Option::from(page);
+        page_params.search_filters = Option::from(filters);
+
+        let sila_handle = params.sila_handle.clone();
+        let message = serde_json::to_string(&GetWebhooksMessage::from(page_params));
+        let signing = message.map(|x| (sign(x.clone()), x));
+
+        async move {
+            let (signing, message) = signing?;
+            let signatures = signing.await;
+
+            get_webhooks(&SignedMessageParams {
+                sila_handle,
+                message,
+                usersignature: signatures.usersignature,
+                authsignature: signatures.authsignature,
+            })
+            .await
+        }
+    })
+    .map_ok(|x| stream::iter(x.webhooks.unwrap_or_default().into_iter().map(Ok)))
+    .try_flatten()
+}
+
+// Collects every undelivered event matching the filters first and only then replays them, so
+// retries that succeed mid-walk don't shift the pages being read.
+pub async fn retry_undelivered_webhooks<F, Fut>(
+    mut filters: WebhookSearchFilters,
+    sign: F,
+) -> Result<
+    Vec<(String, Result<RetryWebhookResponse, Box<dyn std::error::Error + Sync + Send>>)>,
+    Box<dyn std::error::Error + Sync + Send>,
+>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Signatures>,
+{
+    filters.delivered = Option::from(false);
+
+    let undelivered: Vec<Webhook> = get_webhooks_stream(
+        GetWebhooksMessageParams {
+            sila_handle: Option::None,
+            search_filters: Option::from(filters),
+        },
+        &sign,
+    )
+    .try_collect()
+    .await?;
+
+    let mut results = Vec::new();
+
+    for event_uuid in undelivered.into_iter().filter_map(|x| x.uuid) {
+        let message = serde_json::to_string(&RetryWebhookMessage::from(RetryWebhookMessageParams {
+            event_uuid: event_uuid.clone(),
+        }))?;
+        let signatures = sign(message.clone()).await;
+
+        let result = retry_webhook(&SignedMessageParams {
+            sila_handle: Option::None,
+            message,
+            usersignature: signatures.usersignature,
+            authsignature: signatures.authsignature,
+        })
+        .await;
+
+        results.push((event_uuid, result));
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,30 @@
+pub mod get_webhooks;
+pub mod retry_webhook;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Webhook {
+    pub uuid: Option<String>,
+    pub event_type: Option<String>,
+    pub delivered: Option<bool>,
+    pub endpoint_name: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub user_handle: Option<String>,
+    pub attempts: Option<i32>,
+    pub created_epoch: Option<i64>,
+    pub last_attempt_epoch: Option<i64>,
+    pub next_attempt_epoch: Option<i64>,
+    pub payload: Option<serde_json::Value>,
+}
+
+impl std::fmt::Display for Webhook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Webhook(uuid: {}, event_type: {}, delivered: {}, endpoint_name: {})",
+            self.uuid.as_deref().unwrap_or("none"),
+            self.event_type.as_deref().unwrap_or("none"),
+            self.delivered.unwrap_or(false),
+            self.endpoint_name.as_deref().unwrap_or("none")
+        )
+    }
+}
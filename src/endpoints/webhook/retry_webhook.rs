@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    execute, header_message, Header, HeaderMessage, RequiredSignatures, SignedMessageParams,
+    SilaEndpoint, Status,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct RetryWebhookMessage {
+    pub header: Header,
+    pub event_uuid: String,
+}
+
+#[derive(Clone)]
+pub struct RetryWebhookMessageParams {
+    pub event_uuid: String,
+}
+
+impl From<RetryWebhookMessageParams> for RetryWebhookMessage {
+    fn from(params: RetryWebhookMessageParams) -> Self {
+        let sila_params = &*crate::SILA_PARAMS;
+
+        let mut header_message: HeaderMessage = header_message();
+        header_message.header.auth_handle = sila_params.app_handle.clone();
+
+        RetryWebhookMessage {
+            header: header_message.header,
+            event_uuid: params.event_uuid,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RetryWebhookResponse {
+    pub success: bool,
+    pub status: Status,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    pub response_time_ms: Option<String>,
+}
+
+pub struct RetryWebhook;
+
+impl SilaEndpoint for RetryWebhook {
+    type Message = RetryWebhookMessage;
+    type Response = RetryWebhookResponse;
+
+    const PATH: &'static str = "retry_webhook";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::App;
+
+    fn is_failure(response: &RetryWebhookResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+pub async fn retry_webhook(
+    params: &SignedMessageParams,
+) -> Result<RetryWebhookResponse, Box<dyn std::error::Error + Sync + Send>> {
+    execute::<RetryWebhook>(params).await
+}
@@ -1,22 +1,60 @@
-#![feature(async_closure)]
-
+pub mod amount;
+#[cfg(feature = "mock")]
+pub mod cassette;
 pub mod endpoints;
-
+pub mod middleware;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod reconciliation;
+pub mod stats;
+pub mod telemetry;
+pub mod tracker;
+pub mod transport;
+pub mod validation;
+pub mod webhooks;
+
+pub use amount::*;
+pub use endpoints::account::check_instant_ach::*;
+pub use endpoints::account::get_payment_methods::*;
 pub use endpoints::account::link_account::*;
+pub use endpoints::card::delete_card::*;
+pub use endpoints::card::get_cards::*;
+pub use endpoints::card::link_card::*;
+pub use endpoints::card::*;
+pub use endpoints::endpoint::*;
 pub use endpoints::entity::check_kyc::*;
+pub use endpoints::entity::check_partner_kyc::*;
+pub use endpoints::entity::documents::*;
+pub use endpoints::entity::get_entities::*;
 pub use endpoints::entity::register::*;
 pub use endpoints::entity::request_kyc::*;
+pub use endpoints::entity::sms::*;
 pub use endpoints::entity::update::address::*;
 pub use endpoints::entity::update::email::*;
 pub use endpoints::entity::update::identity::*;
 pub use endpoints::entity::update::phone::*;
 pub use endpoints::entity::*;
+pub use endpoints::pagination::*;
+pub use endpoints::transaction::approve_wire::*;
 pub use endpoints::transaction::cancel_transaction::*;
 pub use endpoints::transaction::get_transactions::*;
 pub use endpoints::transaction::issue_sila::*;
 pub use endpoints::transaction::redeem_sila::*;
 pub use endpoints::transaction::transfer_sila::*;
+pub use endpoints::virtual_account::close_virtual_account::*;
+pub use endpoints::virtual_account::get_virtual_account::*;
+pub use endpoints::virtual_account::get_virtual_accounts::*;
+pub use endpoints::virtual_account::open_virtual_account::*;
+pub use endpoints::virtual_account::update_virtual_account::*;
+pub use endpoints::virtual_account::*;
 pub use endpoints::wallet::get_sila_balance::*;
+pub use endpoints::webhook::get_webhooks::*;
+pub use endpoints::webhook::retry_webhook::*;
+pub use endpoints::webhook::*;
+pub use middleware::*;
+pub use tracker::*;
+pub use transport::*;
+pub use validation::*;
 use std::str::FromStr;
 
 use lazy_static::lazy_static;
@@ -26,7 +64,7 @@ use sha3::{Digest, Keccak256};
 use std::convert::TryInto;
 use std::env;
 use std::future::Future;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use uuid::Uuid;
 use web3::{types::H160, types::H256};
 
@@ -36,14 +74,17 @@ pub struct SilaParams {
     pub app_handle: String,
     pub app_address: String,
     pub app_private_key: Option<String>,
+    pub timeout: Duration,
 }
 
 impl SilaParams {
     pub fn new() -> Self {
-        let gateway = match env::var("SILA_ENV") {
-            Ok(x) if x == "PRODUCTION" => "https://api.silamoney.com/0.2".to_string(),
-            Ok(_) => "https://sandbox.silamoney.com/0.2".to_string(),
-            Err(_) => "https://sandbox.silamoney.com/0.2".to_string()
+        // SILA_GATEWAY points the client at any other gateway, e.g. the mock feature's local server
+        let gateway = match (env::var("SILA_GATEWAY"), env::var("SILA_ENV")) {
+            (Ok(x), _) => x.trim_end_matches('/').to_string(),
+            (_, Ok(x)) if x == "PRODUCTION" => "https://api.silamoney.com/0.2".to_string(),
+            (_, Ok(_)) => "https://sandbox.silamoney.com/0.2".to_string(),
+            (_, Err(_)) => "https://sandbox.silamoney.com/0.2".to_string()
         };
 
         let app_private_key = match env::var("SILA_APP_KEY") {
@@ -51,15 +92,28 @@ impl SilaParams {
             Err(_) => Option::None
         };
 
+        let timeout = env::var("SILA_TIMEOUT_SECS")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| Duration::from_secs(30));
+
         SilaParams {
             gateway,
             app_private_key,
+            timeout,
             app_handle: env::var("SILA_APP_HANDLE").expect("SILA_APP_HANDLE must be set"),
             app_address: env::var("SILA_APP_ADDRESS").expect("SILA_APP_ADDRESS must be set"),
         }
     }
 }
 
+impl Default for SilaParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 lazy_static! {
     static ref SILA_PARAMS: SilaParams = {
         SilaParams::new()
@@ -165,13 +219,9 @@ impl From<SignDataParams> for SignDataPair {
         if params.user_params.is_some() {
             let up = params.user_params.clone().unwrap();
 
-            let mut user_private_key = Option::None;
-        
-            if up.private_key.is_some() {
-                user_private_key = Option::from(*H256::from_str(&up.private_key.unwrap())
-                .unwrap()
-                .as_fixed_bytes());
-            }
+            let user_private_key = up
+                .private_key
+                .map(|k| *H256::from_str(&k).unwrap().as_fixed_bytes());
 
             user = Option::from(SignData {
                 address: *H160::from_str(&params.user_params.clone().unwrap().address)
@@ -182,13 +232,10 @@ impl From<SignDataParams> for SignDataPair {
             })
         };
 
-        let mut app_private_key = Option::None;
-
-        if params.app_params.private_key.is_some() {
-            app_private_key = Option::from(*H256::from_str(&params.app_params.private_key.unwrap())
-            .unwrap()
-            .as_fixed_bytes());
-        }
+        let app_private_key = params
+            .app_params
+            .private_key
+            .map(|k| *H256::from_str(&k).unwrap().as_fixed_bytes());
 
         SignDataPair {
             user,
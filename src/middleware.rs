@@ -0,0 +1,77 @@
+use std::any::Any;
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+use reqwest::header::HeaderMap;
+
+// Hooks around every call made through execute. A request reaches the middleware after it has
+// been signed and before it is sent; the raw response reaches it before it is decoded. Like
+// tower layers, on_request runs in the order middleware was added and on_response in reverse.
+
+pub struct SilaRequest<'a> {
+    pub path: &'static str,
+    pub message: &'a (dyn Any + Send + Sync),
+    // the signed message, sent as the body exactly as it was signed
+    pub body: &'a str,
+    pub headers: HeaderMap,
+}
+
+impl SilaRequest<'_> {
+    // The typed message, e.g. request.message::<IssueSilaMessage>() on issue_sila.
+    pub fn message<T: Any>(&self) -> Option<&T> {
+        self.message.downcast_ref::<T>()
+    }
+
+    pub fn user_handle(&self) -> Option<String> {
+        let value: serde_json::Value = serde_json::from_str(self.body).ok()?;
+        value["header"]["user_handle"].as_str().map(|x| x.to_string())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SilaReply {
+    pub status: u16,
+    pub body: String,
+}
+
+pub trait Middleware: Send + Sync {
+    // Err refuses the request; Ok(Some(reply)) answers it without sending (fault injection,
+    // fixtures), and the reply then goes through on_response like a real one.
+    fn on_request(
+        &self,
+        _request: &mut SilaRequest,
+    ) -> Result<Option<SilaReply>, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Option::None)
+    }
+
+    // May rewrite the reply before it is decoded; Err fails the call.
+    fn on_response(
+        &self,
+        _request: &SilaRequest,
+        _reply: &mut SilaReply,
+    ) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        Ok(())
+    }
+}
+
+lazy_static! {
+    static ref MIDDLEWARE: RwLock<Vec<Arc<dyn Middleware>>> = RwLock::new(Vec::new());
+}
+
+/// Adds middleware to the end of the chain run around every call made through `execute`.
+///
+/// The chain is process-wide, like the `SILA_*` settings: there is no client object to register
+/// it on, so middleware added here sees the calls of every caller in the process, including other
+/// tests running in the same binary.
+pub fn add_middleware(middleware: Arc<dyn Middleware>) {
+    MIDDLEWARE.write().unwrap_or_else(|e| e.into_inner()).push(middleware);
+}
+
+/// Removes all middleware from the process-wide chain, including any added by other callers.
+pub fn clear_middleware() {
+    MIDDLEWARE.write().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+pub(crate) fn chain() -> Vec<Arc<dyn Middleware>> {
+    MIDDLEWARE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
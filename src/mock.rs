@@ -0,0 +1,1899 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::Infallible;
+use std::convert::TryInto;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use log::error;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::Secp256k1;
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+use web3::types::H160;
+
+use crate::transport::signed_hash;
+use crate::{
+    AddPhone, AddPhoneMessage, Address, ApproveWire, ApproveWireMessage, CancelTransaction,
+    CancelTransactionMessage, Card, CheckHandle, CheckInstantAch, CheckInstantAchMessage, CheckKyc,
+    CheckPartnerKyc, CheckPartnerKycMessage, CloseVirtualAccount, CloseVirtualAccountMessage,
+    ConfirmSms, ConfirmSmsMessage, DeleteCard, DocumentMessage, DeleteCardMessage, EmailResponse, EntityType,
+    GetCards, GetEntities, GetEntitiesMessage, GetEntity, GetPaymentMethods,
+    GetPaymentMethodsMessage, GetSilaBalance, GetSilaBalanceMessage, GetTransactions,
+    GetTransactionsMessage, GetVirtualAccount, GetVirtualAccountMessage, GetVirtualAccounts,
+    GetWebhooks, GetWebhooksMessage, Header, IdentityResponse, IssueSila, IssueSilaMessage,
+    LinkAccount, LinkCard, LinkCardMessage, LinkMessage, OpenVirtualAccount,
+    OpenVirtualAccountMessage, PaymentMethodType, PhoneResponse, RedeemSila, RedeemSilaMessage,
+    Register, RegisterMessage, RequestKyc, RequestSmsConfirmation, RequiredSignatures,
+    RetryWebhook, RetryWebhookMessage, SilaAmount, SilaEndpoint, SmsConfirmationMessage,
+    TransactionStatus, TransactionType, TransferSila, TransferSilaMessage, UpdateAddress,
+    UpdateAddressMessage, UpdateEmail, UpdateEmailMessage, UpdateIdentity, UpdateIdentityMessage,
+    UpdatePhone, UpdatePhoneMessage, UpdateVirtualAccount, UpdateVirtualAccountMessage,
+    UploadDocument, VirtualAccount, Webhook,
+};
+use crate::sha256_hex;
+
+// An in-process Sila gateway for offline testing. Point the client at it by setting SILA_GATEWAY
+// to MockGateway::url() before SILA_PARAMS is first used. Every request must carry an
+// authsignature from the app address the gateway was started with; user requests must also be
+// signed by the address the handle was registered with.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MockKycStatus {
+    Pending,
+    Passed,
+    Failed,
+}
+
+impl std::fmt::Display for MockKycStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            MockKycStatus::Pending => write!(f, "pending"),
+            MockKycStatus::Passed => write!(f, "passed"),
+            MockKycStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+struct MockEntity {
+    address: H160,
+    kyc: Option<VecDeque<MockKycStatus>>,
+    accounts: HashSet<String>,
+    balance: SilaAmount,
+    created_epoch: i64,
+    entity_name: String,
+    first_name: String,
+    last_name: String,
+    birthdate: Option<String>,
+    addresses: Vec<Address>,
+    identities: Vec<IdentityResponse>,
+    emails: Vec<EmailResponse>,
+    phones: Vec<PhoneResponse>,
+    // confirmation codes sent by request_sms_confirmation, by phone uuid
+    sms_codes: HashMap<String, String>,
+    cards: Vec<Card>,
+    virtual_accounts: Vec<VirtualAccount>,
+    documents: Vec<String>,
+}
+
+impl MockEntity {
+    fn kyc_status(&self) -> Option<MockKycStatus> {
+        self.kyc.as_ref().and_then(|x| x.front().copied())
+    }
+}
+
+struct MockTransaction {
+    transaction_id: String,
+    reference_id: String,
+    user_handle: String,
+    transaction_type: TransactionType,
+    amount: SilaAmount,
+    status: TransactionStatus,
+    // the statuses still to come, one per poll of get_transactions
+    remaining: VecDeque<TransactionStatus>,
+    destination_handle: Option<String>,
+    bank_account_name: Option<String>,
+    descriptor: Option<String>,
+    created_epoch: i64,
+    last_update_epoch: i64,
+    // redeem and transfer debit the sender on submission and refund it if they fail
+    debited: bool,
+    settled: bool,
+}
+
+impl MockTransaction {
+    fn to_json(&self) -> Value {
+        json!({
+            "user_handle": self.user_handle,
+            "reference_id": self.reference_id,
+            "transaction_id": self.transaction_id,
+            "transaction_type": self.transaction_type,
+            "sila_amount": self.amount,
+            "status": self.status,
+            "created_epoch": self.created_epoch,
+            "last_update_epoch": self.last_update_epoch,
+            "descriptor": self.descriptor,
+            "bank_account_name": self.bank_account_name,
+            "destination_handle": self.destination_handle,
+        })
+    }
+}
+
+struct MockState {
+    app_handle: String,
+    app_address: H160,
+    entities: HashMap<String, MockEntity>,
+    transactions: Vec<MockTransaction>,
+    kyc_script: Vec<MockKycStatus>,
+    transaction_script: Vec<TransactionStatus>,
+    // the mock never delivers webhooks, so every event stays undelivered until retried
+    webhooks: Vec<Webhook>,
+}
+
+pub(crate) const VERSION: &str = "0.2";
+
+type Reply = (StatusCode, Value);
+
+struct MockRequest<'a> {
+    header: Header,
+    // the JSON message, or the "data" part of a multipart body
+    body: &'a [u8],
+    query: &'a str,
+    usersignature: Option<&'a str>,
+    // the other parts of a multipart body, by part name
+    files: &'a [(String, Vec<u8>)],
+}
+
+type Handler = fn(&mut MockState, &MockRequest) -> Reply;
+
+struct Route {
+    path: &'static str,
+    signatures: RequiredSignatures,
+    handler: Handler,
+}
+
+fn route<E: SilaEndpoint>(handler: Handler) -> Route {
+    Route {
+        path: E::PATH,
+        signatures: E::SIGNATURES,
+        handler,
+    }
+}
+
+// Every endpoint the crate supports, keyed on its full path so update/phone and add/phone stay
+// apart. The signatures an endpoint is sent with decide whether the gateway requires the user's.
+fn routes() -> Vec<Route> {
+    vec![
+        route::<CheckHandle>(|s, r| s.check_handle(&r.header)),
+        route::<Register>(|s, r| s.register(r.body, r.usersignature)),
+        route::<GetEntity>(|s, r| s.get_entity(&r.header)),
+        route::<GetEntities>(|s, r| s.get_entities(r.body, r.query)),
+        route::<RequestKyc>(|s, r| s.request_kyc(&r.header)),
+        route::<CheckKyc>(|s, r| s.check_kyc(&r.header)),
+        route::<CheckPartnerKyc>(|s, r| s.check_partner_kyc(r.body)),
+        route::<UpdateAddress>(|s, r| s.update_address(r.body)),
+        route::<UpdateEmail>(|s, r| s.update_email(r.body)),
+        route::<UpdateIdentity>(|s, r| s.update_identity(r.body)),
+        route::<UpdatePhone>(|s, r| s.update_phone(r.body)),
+        route::<AddPhone>(|s, r| s.add_phone(r.body)),
+        route::<RequestSmsConfirmation>(|s, r| s.request_sms_confirmation(r.body)),
+        route::<ConfirmSms>(|s, r| s.confirm_sms(r.body)),
+        route::<LinkAccount>(|s, r| s.link_account(r.body)),
+        route::<CheckInstantAch>(|s, r| s.check_instant_ach(r.body)),
+        route::<GetPaymentMethods>(|s, r| s.get_payment_methods(r.body)),
+        route::<LinkCard>(|s, r| s.link_card(r.body)),
+        route::<GetCards>(|s, r| s.get_cards(&r.header)),
+        route::<DeleteCard>(|s, r| s.delete_card(r.body)),
+        route::<OpenVirtualAccount>(|s, r| s.open_virtual_account(r.body)),
+        route::<GetVirtualAccount>(|s, r| s.get_virtual_account(r.body)),
+        route::<GetVirtualAccounts>(|s, r| s.get_virtual_accounts(&r.header)),
+        route::<UpdateVirtualAccount>(|s, r| s.update_virtual_account(r.body)),
+        route::<CloseVirtualAccount>(|s, r| s.close_virtual_account(r.body)),
+        route::<GetSilaBalance>(|s, r| s.get_sila_balance(r.body)),
+        route::<IssueSila>(|s, r| s.issue_sila(r.body)),
+        route::<RedeemSila>(|s, r| s.redeem_sila(r.body)),
+        route::<TransferSila>(|s, r| s.transfer_sila(r.body)),
+        route::<CancelTransaction>(|s, r| s.cancel_transaction(r.body)),
+        route::<GetTransactions>(|s, r| s.get_transactions(r.body)),
+        route::<ApproveWire>(|s, r| s.approve_wire(r.body)),
+        route::<GetWebhooks>(|s, r| s.get_webhooks(r.body)),
+        route::<RetryWebhook>(|s, r| s.retry_webhook(r.body)),
+        route::<UploadDocument>(|s, r| s.upload_document(r.body, r.files)),
+    ]
+}
+
+fn find_route(path: &str) -> Option<Route> {
+    routes().into_iter().find(|x| x.path == path)
+}
+
+fn query_param(query: &str, name: &str) -> Option<usize> {
+    query
+        .split('&')
+        .filter_map(|x| x.split_once('='))
+        .find(|(key, _)| *key == name)
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+// Sila returns identity numbers masked to their last four digits.
+fn mask(value: &str) -> String {
+    let visible = value.len().saturating_sub(4);
+    format!("{}{}", "*".repeat(visible), &value[visible..])
+}
+
+fn paginate_json(items: Vec<Value>, page: usize, per_page: usize) -> (Vec<Value>, Value) {
+    let per_page = per_page.max(1);
+    let page = page.max(1);
+    let total_count = items.len();
+    let total_pages = total_count.div_ceil(per_page).max(1);
+
+    let items: Vec<Value> = items.into_iter().skip((page - 1) * per_page).take(per_page).collect();
+    let pagination = json!({
+        "returned_count": items.len(),
+        "total_count": total_count,
+        "current_page": page,
+        "total_pages": total_pages,
+    });
+
+    (items, pagination)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|x| x == needle)
+}
+
+// The named parts of a multipart/form-data body; the mock has no use for their other headers.
+fn multipart_parts(content_type: &str, body: &[u8]) -> Option<Vec<(String, Vec<u8>)>> {
+    let boundary = format!("--{}", content_type.split("boundary=").nth(1)?.trim_matches('"'));
+    let boundary = boundary.as_bytes();
+
+    let mut rest = &body[find(body, boundary)? + boundary.len()..];
+    let mut parts = Vec::new();
+
+    while !rest.starts_with(b"--") {
+        let end = find(rest, boundary)?;
+        let part = rest[..end].strip_prefix(b"\r\n")?.strip_suffix(b"\r\n")?;
+        let split = find(part, b"\r\n\r\n")?;
+
+        let headers = std::str::from_utf8(&part[..split]).ok()?;
+        let name = headers.split("name=\"").nth(1)?.split('"').next()?;
+        parts.push((name.to_string(), part[split + 4..].to_vec()));
+
+        rest = &rest[end + boundary.len()..];
+    }
+
+    Some(parts)
+}
+
+fn now_epoch() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|x| x.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn failure(code: StatusCode, reference: &str, message: &str) -> Reply {
+    (
+        code,
+        json!({
+            "success": false,
+            "status": "FAILURE",
+            "message": message,
+            "reference": reference,
+        }),
+    )
+}
+
+fn success(reference: &str, message: &str, extra: Value) -> Reply {
+    let mut body = json!({
+        "success": true,
+        "status": "SUCCESS",
+        "message": message,
+        "reference": reference,
+    });
+
+    if let (Some(body), Value::Object(extra)) = (body.as_object_mut(), extra) {
+        body.extend(extra);
+    }
+
+    (StatusCode::OK, body)
+}
+
+// Recovers the signing address from an Ethereum-style (r || s || v) signature over keccak256 of
+// the request body.
+pub fn recover_address(message: &[u8], signature: &str) -> Result<H160, Box<dyn std::error::Error + Sync + Send>> {
+    let bytes = hex::decode(signature.trim_start_matches("0x"))?;
+
+    if bytes.len() != 65 {
+        return Err(Box::from("signature must be 65 bytes"));
+    }
+
+    let v = match bytes[64] {
+        x @ 0x1b..=0x1c => x - 0x1b,
+        x @ 0..=1 => x,
+        _ => return Err(Box::from("invalid signature recovery id")),
+    };
+
+    let recovery_id = RecoveryId::from_i32(v as i32)?;
+    let signature = RecoverableSignature::from_compact(&bytes[0..64], recovery_id)?;
+    let message = secp256k1::Message::from_slice(&keccak256(message))?;
+
+    let public_key = Secp256k1::new().recover_ecdsa(&message, &signature)?;
+    let public_key = public_key.serialize_uncompressed();
+    let hash = keccak256(&public_key[1..]);
+
+    Ok(H160::from_slice(&hash[12..]))
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data)
+        .as_slice()
+        .try_into()
+        .expect("Wrong length")
+}
+
+impl MockState {
+    fn new(app_handle: String, app_address: H160) -> Self {
+        MockState {
+            app_handle,
+            app_address,
+            entities: HashMap::new(),
+            transactions: Vec::new(),
+            kyc_script: vec![MockKycStatus::Pending, MockKycStatus::Passed],
+            transaction_script: vec![
+                TransactionStatus::Queued,
+                TransactionStatus::Pending,
+                TransactionStatus::Success,
+            ],
+            webhooks: Vec::new(),
+        }
+    }
+
+    fn dispatch(
+        &mut self,
+        endpoint: &str,
+        query: &str,
+        body: &[u8],
+        files: &[(String, Vec<u8>)],
+        authsignature: Option<&str>,
+        usersignature: Option<&str>,
+    ) -> Reply {
+        let header: Header = match serde_json::from_slice::<Value>(body)
+            .and_then(|x| serde_json::from_value(x["header"].clone()))
+        {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &format!("invalid request body: {}", e)),
+        };
+        let reference = header.reference.clone();
+
+        if header.auth_handle != self.app_handle {
+            return failure(StatusCode::FORBIDDEN, &reference, "unknown auth_handle");
+        }
+
+        match authsignature.map(|x| recover_address(body, x)) {
+            Some(Ok(x)) if x == self.app_address => (),
+            _ => return failure(StatusCode::UNAUTHORIZED, &reference, "authsignature does not match the app address"),
+        }
+
+        let route = match find_route(endpoint) {
+            Some(x) => x,
+            None => {
+                return failure(StatusCode::NOT_FOUND, &reference, &format!("{} is not implemented by the mock gateway", endpoint))
+            }
+        };
+
+        // register checks the user signature against the address being registered instead
+        let user_required = route.signatures == RequiredSignatures::User;
+
+        if endpoint != Register::PATH {
+            if let Some(handle) = &header.user_handle {
+                if let Some(entity) = self.entities.get(handle) {
+                    match usersignature.map(|x| recover_address(body, x)) {
+                        Some(Ok(x)) if x == entity.address => (),
+                        None if !user_required => (),
+                        _ => return failure(StatusCode::UNAUTHORIZED, &reference, "usersignature does not match the registered address"),
+                    }
+                } else if endpoint != CheckHandle::PATH {
+                    return failure(StatusCode::BAD_REQUEST, &reference, &format!("unknown user handle {}", handle));
+                }
+            } else if user_required {
+                return failure(StatusCode::BAD_REQUEST, &reference, "user_handle is required");
+            }
+        }
+
+        let request = MockRequest {
+            header,
+            body,
+            query,
+            usersignature,
+            files,
+        };
+
+        (route.handler)(self, &request)
+    }
+
+    // Only for handlers whose route requires the user signature, which dispatch has checked.
+    fn entity_mut(&mut self, header: &Header) -> &mut MockEntity {
+        self.entities
+            .get_mut(header.user_handle.as_deref().unwrap_or_default())
+            .expect("handle was validated on dispatch")
+    }
+
+    fn notify(&mut self, event_type: &str, user_handle: &str, payload: Value) {
+        self.webhooks.push(Webhook {
+            uuid: Option::from(Uuid::new_v4().to_string()),
+            event_type: Option::from(event_type.to_string()),
+            delivered: Option::from(false),
+            endpoint_name: Option::from("default".to_string()),
+            endpoint_url: Option::None,
+            user_handle: Option::from(user_handle.to_string()),
+            attempts: Option::from(0),
+            created_epoch: Option::from(now_epoch()),
+            last_attempt_epoch: Option::None,
+            next_attempt_epoch: Option::None,
+            payload: Option::from(payload),
+        });
+    }
+
+    fn check_handle(&self, header: &Header) -> Reply {
+        match &header.user_handle {
+            Some(x) if self.entities.contains_key(x) => {
+                failure(StatusCode::OK, &header.reference, &format!("{} is already taken", x))
+            }
+            Some(x) => success(&header.reference, &format!("{} is available", x), json!({})),
+            None => failure(StatusCode::BAD_REQUEST, &header.reference, "user_handle is required"),
+        }
+    }
+
+    fn register(&mut self, body: &[u8], usersignature: Option<&str>) -> Reply {
+        let message: RegisterMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+
+        let handle = match message.header.user_handle {
+            Some(x) => x,
+            None => return failure(StatusCode::BAD_REQUEST, &reference, "user_handle is required"),
+        };
+
+        if self.entities.contains_key(&handle) {
+            return failure(StatusCode::OK, &reference, &format!("{} is already taken", handle));
+        }
+
+        let address = match message.crypto_entry.crypto_address.parse::<H160>() {
+            Ok(x) => x,
+            Err(_) => return failure(StatusCode::BAD_REQUEST, &reference, "invalid crypto_address"),
+        };
+
+        match usersignature.map(|x| recover_address(body, x)) {
+            Some(Ok(x)) if x == address => (),
+            _ => return failure(StatusCode::UNAUTHORIZED, &reference, "usersignature does not match crypto_address"),
+        }
+
+        let now = now_epoch();
+        let contact = message.contact.unwrap_or_default();
+
+        let identities = message
+            .identity
+            .map(|x| IdentityResponse {
+                added_epoch: Option::from(now),
+                modified_epoch: Option::from(now),
+                uuid: Option::from(Uuid::new_v4().to_string()),
+                identity_type: serde_json::to_value(&x.identity_alias)
+                    .ok()
+                    .and_then(|x| x.as_str().map(|x| x.to_string())),
+                identity: Option::from(mask(&x.identity_value)),
+            })
+            .into_iter()
+            .collect();
+
+        let emails = contact
+            .email
+            .map(|x| EmailResponse {
+                added_epoch: Option::from(now),
+                modified_epoch: Option::from(now),
+                uuid: Option::from(Uuid::new_v4().to_string()),
+                email: Option::from(x),
+            })
+            .into_iter()
+            .collect();
+
+        let phones = contact
+            .phone
+            .map(|x| PhoneResponse {
+                added_epoch: Option::from(now),
+                modified_epoch: Option::from(now),
+                uuid: Option::from(Uuid::new_v4().to_string()),
+                phone: Option::from(x),
+                sms_confirmation_requested: Option::from(false),
+                sms_confirmed: Option::from(false),
+                primary: Option::from(true),
+            })
+            .into_iter()
+            .collect();
+
+        let addresses = message
+            .address
+            .map(|x| Address {
+                added_epoch: Option::from(now),
+                modified_epoch: Option::from(now),
+                uuid: Option::from(Uuid::new_v4().to_string()),
+                ..x
+            })
+            .into_iter()
+            .collect();
+
+        self.entities.insert(
+            handle.clone(),
+            MockEntity {
+                address,
+                kyc: Option::None,
+                accounts: HashSet::new(),
+                balance: SilaAmount::ZERO,
+                created_epoch: now,
+                entity_name: message.entity.entity_name,
+                first_name: message.entity.first_name,
+                last_name: message.entity.last_name,
+                birthdate: message.entity.birthdate,
+                addresses,
+                identities,
+                emails,
+                phones,
+                sms_codes: HashMap::new(),
+                cards: Vec::new(),
+                virtual_accounts: Vec::new(),
+                documents: Vec::new(),
+            },
+        );
+
+        success(&reference, &format!("{} was successfully registered", handle), json!({}))
+    }
+
+    fn get_entity(&self, header: &Header) -> Reply {
+        let handle = match &header.user_handle {
+            Some(x) => x,
+            None => return failure(StatusCode::BAD_REQUEST, &header.reference, "user_handle is required"),
+        };
+        let entity = &self.entities[handle];
+
+        success(
+            &header.reference,
+            "entity retrieved",
+            json!({
+                "user_handle": handle,
+                "entity_type": "individual",
+                "entity": {
+                    "created_epoch": entity.created_epoch,
+                    "entity_name": entity.entity_name,
+                    "birthdate": entity.birthdate,
+                    "first_name": entity.first_name,
+                    "last_name": entity.last_name,
+                    "type": "individual",
+                },
+                "addresses": entity.addresses,
+                "identities": entity.identities,
+                "emails": entity.emails,
+                "phones": entity.phones,
+                "devices": [],
+                "memberships": [],
+            }),
+        )
+    }
+
+    // page and per_page come from the query string, as they do on the real gateway.
+    fn get_entities(&self, body: &[u8], query: &str) -> Reply {
+        let message: GetEntitiesMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+
+        // the mock only registers individuals
+        let mut handles: Vec<&String> = match message.entity_type {
+            Some(EntityType::Business) => Vec::new(),
+            _ => self.entities.keys().collect(),
+        };
+        handles.sort();
+
+        let individuals: Vec<Value> = handles
+            .into_iter()
+            .map(|x| {
+                let entity = &self.entities[x];
+                json!({
+                    "handle": x,
+                    "full_name": format!("{} {}", entity.first_name, entity.last_name),
+                    "created_epoch": entity.created_epoch,
+                    "status": entity.kyc_status().map(|x| x.to_string()).unwrap_or_else(|| "unverified".to_string()),
+                    "blockchain_addresses": [format!("{:#x}", entity.address)],
+                })
+            })
+            .collect();
+
+        let (individuals, pagination) = paginate_json(
+            individuals,
+            query_param(query, "page").unwrap_or(1),
+            query_param(query, "per_page").unwrap_or(20),
+        );
+
+        success(
+            &reference,
+            "entities retrieved",
+            json!({
+                "entities": { "individuals": individuals, "businesses": [] },
+                "pagination": pagination,
+            }),
+        )
+    }
+
+    fn check_partner_kyc(&self, body: &[u8]) -> Reply {
+        let message: CheckPartnerKycMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+
+        if message.query_app_handle != self.app_handle {
+            return failure(StatusCode::OK, &reference, &format!("unknown app handle {}", message.query_app_handle));
+        }
+
+        let status = match self.entities.get(&message.query_user_handle) {
+            Some(x) => x.kyc_status(),
+            None => {
+                return failure(StatusCode::OK, &reference, &format!("unknown user handle {}", message.query_user_handle))
+            }
+        };
+
+        let (_, mut body) = match status {
+            Some(MockKycStatus::Passed) => success(&reference, "user has passed ID verification", json!({})),
+            Some(x) => failure(StatusCode::OK, &reference, &format!("user verification is {}", x)),
+            None => failure(StatusCode::OK, &reference, "KYC has not been requested"),
+        };
+
+        body["entity_type"] = json!("individual");
+        body["kyc_level"] = json!("DEFAULT");
+        body["verification_status"] = json!(status.map(|x| x.to_string()).unwrap_or_else(|| "unverified".to_string()));
+
+        (StatusCode::OK, body)
+    }
+
+    fn update_address(&mut self, body: &[u8]) -> Reply {
+        let message: UpdateAddressMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        let address = match entity.addresses.iter_mut().find(|x| x.uuid.as_ref() == Some(&message.uuid)) {
+            Some(x) => x,
+            None => return failure(StatusCode::OK, &reference, &format!("unknown address {}", message.uuid)),
+        };
+
+        address.address_alias = message.address_alias.or(address.address_alias.take());
+        address.street_address_1 = message.street_address_1.or(address.street_address_1.take());
+        address.street_address_2 = message.street_address_2.or(address.street_address_2.take());
+        address.city = message.city.or(address.city.take());
+        address.state = message.state.or(address.state.take());
+        address.postal_code = message.postal_code.or(address.postal_code.take());
+        address.country = message.country.or(address.country.take());
+        address.modified_epoch = Option::from(now_epoch());
+
+        success(&reference, "address updated", json!({ "address": address }))
+    }
+
+    fn update_email(&mut self, body: &[u8]) -> Reply {
+        let message: UpdateEmailMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        match entity.emails.iter_mut().find(|x| x.uuid.as_ref() == Some(&message.uuid)) {
+            Some(x) => {
+                x.email = Option::from(message.email);
+                x.modified_epoch = Option::from(now_epoch());
+                success(&reference, "email updated", json!({ "email": x }))
+            }
+            None => failure(StatusCode::OK, &reference, &format!("unknown email {}", message.uuid)),
+        }
+    }
+
+    fn update_identity(&mut self, body: &[u8]) -> Reply {
+        let message: UpdateIdentityMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        match entity.identities.iter_mut().find(|x| x.uuid.as_ref() == Some(&message.uuid)) {
+            Some(x) => {
+                x.identity_type = Option::from(message.identity_alias);
+                x.identity = Option::from(mask(&message.identity_value));
+                x.modified_epoch = Option::from(now_epoch());
+                success(&reference, "identity updated", json!({ "identity": x }))
+            }
+            None => failure(StatusCode::OK, &reference, &format!("unknown identity {}", message.uuid)),
+        }
+    }
+
+    // A changed number has to be confirmed again.
+    fn update_phone(&mut self, body: &[u8]) -> Reply {
+        let message: UpdatePhoneMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        match entity.phones.iter_mut().find(|x| x.uuid.as_ref() == Some(&message.uuid)) {
+            Some(x) => {
+                if message.phone.is_some() && message.phone != x.phone {
+                    x.phone = message.phone;
+                    x.sms_confirmation_requested = Option::from(false);
+                    x.sms_confirmed = Option::from(false);
+                    entity.sms_codes.remove(&message.uuid);
+                }
+                x.modified_epoch = Option::from(now_epoch());
+                success(&reference, "phone updated", json!({ "phone": x }))
+            }
+            None => failure(StatusCode::OK, &reference, &format!("unknown phone {}", message.uuid)),
+        }
+    }
+
+    fn add_phone(&mut self, body: &[u8]) -> Reply {
+        let message: AddPhoneMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+        let now = now_epoch();
+
+        entity.phones.push(PhoneResponse {
+            added_epoch: Option::from(now),
+            modified_epoch: Option::from(now),
+            uuid: Option::from(Uuid::new_v4().to_string()),
+            phone: Option::from(message.phone),
+            sms_confirmation_requested: Option::from(false),
+            sms_confirmed: Option::from(false),
+            primary: Option::from(entity.phones.is_empty()),
+        });
+
+        success(&reference, "phone added", json!({ "phone": entity.phones.last() }))
+    }
+
+    // The code that would be texted is kept for MockGateway::sms_code.
+    fn request_sms_confirmation(&mut self, body: &[u8]) -> Reply {
+        let message: SmsConfirmationMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        let phone = match entity.phones.iter_mut().find(|x| x.uuid.as_ref() == Some(&message.uuid)) {
+            Some(x) if x.is_confirmed() => return failure(StatusCode::OK, &reference, "phone is already confirmed"),
+            Some(x) => x,
+            None => return failure(StatusCode::OK, &reference, &format!("unknown phone {}", message.uuid)),
+        };
+
+        phone.sms_confirmation_requested = Option::from(true);
+        phone.modified_epoch = Option::from(now_epoch());
+        let reply = success(&reference, "confirmation code sent", json!({ "phone": phone }));
+
+        let code = format!("{:06}", Uuid::new_v4().as_u128() % 1_000_000);
+        entity.sms_codes.insert(message.uuid, code);
+
+        reply
+    }
+
+    fn confirm_sms(&mut self, body: &[u8]) -> Reply {
+        let message: ConfirmSmsMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        if entity.sms_codes.get(&message.uuid) != Some(&message.code) {
+            return failure(StatusCode::OK, &reference, "invalid confirmation code");
+        }
+
+        entity.sms_codes.remove(&message.uuid);
+
+        match entity.phones.iter_mut().find(|x| x.uuid.as_ref() == Some(&message.uuid)) {
+            Some(x) => {
+                x.sms_confirmed = Option::from(true);
+                x.modified_epoch = Option::from(now_epoch());
+                success(&reference, "phone confirmed", json!({ "phone": x }))
+            }
+            None => failure(StatusCode::OK, &reference, &format!("unknown phone {}", message.uuid)),
+        }
+    }
+
+    fn request_kyc(&mut self, header: &Header) -> Reply {
+        let script = self.kyc_script.clone();
+        let handle = header.user_handle.clone().unwrap_or_default();
+
+        match self.entities.get_mut(&handle) {
+            Some(x) => {
+                x.kyc = Option::from(script.into_iter().collect::<VecDeque<_>>());
+                self.notify("kyc", &handle, json!({ "entity": handle, "outcome": "pending" }));
+                success(&header.reference, "user submitted for KYC review", json!({}))
+            }
+            None => failure(StatusCode::BAD_REQUEST, &header.reference, "unknown user handle"),
+        }
+    }
+
+    fn check_kyc(&mut self, header: &Header) -> Reply {
+        let entity = match self.entities.get_mut(header.user_handle.as_deref().unwrap_or_default()) {
+            Some(x) => x,
+            None => return failure(StatusCode::BAD_REQUEST, &header.reference, "unknown user handle"),
+        };
+
+        let status = match entity.kyc.as_mut() {
+            Some(script) => {
+                let status = script.front().copied();
+
+                // the last step of the script sticks
+                if script.len() > 1 {
+                    script.pop_front();
+                }
+
+                status
+            }
+            None => return failure(StatusCode::OK, &header.reference, "KYC has not been requested"),
+        };
+
+        match status {
+            Some(MockKycStatus::Passed) => success(
+                &header.reference,
+                "user has passed ID verification",
+                json!({ "verification_status": "passed" }),
+            ),
+            Some(x) => {
+                let (_, mut body) = failure(StatusCode::OK, &header.reference, &format!("user verification is {}", x));
+                body["verification_status"] = json!(x.to_string());
+                (StatusCode::OK, body)
+            }
+            None => failure(StatusCode::OK, &header.reference, "KYC has not been requested"),
+        }
+    }
+
+    fn link_account(&mut self, body: &[u8]) -> Reply {
+        let message: LinkMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let handle = message.header.user_handle.unwrap_or_default();
+
+        match self.entities.get_mut(&handle) {
+            Some(x) if x.accounts.contains(&message.account_name) => {
+                failure(StatusCode::OK, &reference, &format!("account {} is already linked", message.account_name))
+            }
+            Some(x) => {
+                x.accounts.insert(message.account_name.clone());
+                success(
+                    &reference,
+                    "bank account successfully linked",
+                    json!({
+                        "account_name": message.account_name,
+                        "match_score": 1.0,
+                        "web_debit_verified": true,
+                    }),
+                )
+            }
+            None => failure(StatusCode::BAD_REQUEST, &reference, "unknown user handle"),
+        }
+    }
+
+    fn check_instant_ach(&mut self, body: &[u8]) -> Reply {
+        let message: CheckInstantAchMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        let mut details = serde_json::Map::new();
+
+        if entity.kyc_status() != Some(MockKycStatus::Passed) {
+            details.insert("kyc".to_string(), json!("entity has not passed KYC"));
+        }
+        if !entity.accounts.contains(&message.account_name) {
+            details.insert("account".to_string(), json!(format!("no linked account named {}", message.account_name)));
+        }
+
+        if details.is_empty() {
+            return success(&reference, "account is eligible for instant ACH", json!({}));
+        }
+
+        let (_, mut body) = failure(StatusCode::OK, &reference, "account is not eligible for instant ACH");
+        body["validation_details"] = Value::Object(details);
+        (StatusCode::OK, body)
+    }
+
+    fn get_payment_methods(&mut self, body: &[u8]) -> Reply {
+        let message: GetPaymentMethodsMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let types = message.search_filters.and_then(|x| x.payment_method_types);
+        let wanted = |x: PaymentMethodType| types.as_ref().is_none_or(|t| t.contains(&x));
+        let entity = self.entity_mut(&message.header);
+
+        let mut methods: Vec<Value> = Vec::new();
+
+        if wanted(PaymentMethodType::BankAccount) {
+            let mut accounts: Vec<&String> = entity.accounts.iter().collect();
+            accounts.sort();
+
+            methods.extend(accounts.into_iter().map(|x| {
+                json!({
+                    "payment_method_type": "bank_account",
+                    "account_name": x,
+                    "account_status": "active",
+                    "account_owner_name": format!("{} {}", entity.first_name, entity.last_name),
+                    "account_link_status": "processor_token",
+                    "active": true,
+                    "match_score": 1.0,
+                })
+            }));
+        }
+        if wanted(PaymentMethodType::BlockchainAddress) {
+            methods.push(json!({
+                "payment_method_type": "blockchain_address",
+                "blockchain_address": format!("{:#x}", entity.address),
+                "blockchain_network": "ETH",
+                "nickname": "default",
+                "default": true,
+            }));
+        }
+        if wanted(PaymentMethodType::VirtualAccount) {
+            methods.extend(entity.virtual_accounts.iter().filter(|x| x.closed != Some(true)).map(|x| {
+                let mut method = json!(x);
+                method["payment_method_type"] = json!("virtual_account");
+                method
+            }));
+        }
+        if wanted(PaymentMethodType::Card) {
+            methods.extend(entity.cards.iter().map(|x| {
+                json!({
+                    "payment_method_type": "card",
+                    "card_id": x.card_id,
+                    "card_name": x.card_name,
+                    "last_4": x.last_4,
+                    "expiration": x.expiration,
+                    "card_network": x.card_network,
+                    "card_type": x.card_type,
+                    "active": x.active,
+                })
+            }));
+        }
+
+        success(&reference, "payment methods retrieved", json!({ "payment_methods": methods }))
+    }
+
+    fn link_card(&mut self, body: &[u8]) -> Reply {
+        let message: LinkCardMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        if entity.cards.iter().any(|x| x.card_name.as_ref() == Some(&message.card_name)) {
+            return failure(StatusCode::OK, &reference, &format!("card {} is already linked", message.card_name));
+        }
+
+        let last_4: String = message.token.chars().rev().take(4).collect::<Vec<_>>().into_iter().rev().collect();
+
+        entity.cards.push(Card {
+            card_id: Option::from(Uuid::new_v4().to_string()),
+            card_name: Option::from(message.card_name.clone()),
+            last_4: Option::from(last_4),
+            expiration: Option::from("12/2030".to_string()),
+            card_network: Option::from("visa".to_string()),
+            card_type: Option::from("debit".to_string()),
+            card_status: Option::from("active".to_string()),
+            active: Option::from(true),
+            provider: message.provider,
+        });
+
+        success(&reference, "card successfully linked", json!({ "card_name": message.card_name, "avs": "Y" }))
+    }
+
+    fn get_cards(&mut self, header: &Header) -> Reply {
+        let entity = self.entity_mut(header);
+        success(&header.reference, "cards retrieved", json!({ "cards": entity.cards }))
+    }
+
+    fn delete_card(&mut self, body: &[u8]) -> Reply {
+        let message: DeleteCardMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        match entity.cards.iter().position(|x| x.card_name.as_ref() == Some(&message.card_name)) {
+            Some(i) => {
+                entity.cards.remove(i);
+                success(&reference, "card deleted", json!({ "card_name": message.card_name }))
+            }
+            None => failure(StatusCode::OK, &reference, &format!("no linked card named {}", message.card_name)),
+        }
+    }
+
+    fn open_virtual_account(&mut self, body: &[u8]) -> Reply {
+        let message: OpenVirtualAccountMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+
+        if let Some(x) = self.require_kyc(message.header.user_handle.as_deref().unwrap_or_default(), &reference) {
+            return x;
+        }
+
+        let entity = self.entity_mut(&message.header);
+        let account_number = format!("{:010}", Uuid::new_v4().as_u128() % 10_000_000_000);
+
+        entity.virtual_accounts.push(VirtualAccount {
+            virtual_account_id: Option::from(Uuid::new_v4().to_string()),
+            virtual_account_name: Option::from(message.virtual_account_name),
+            account_number: Option::from(account_number),
+            routing_number: Option::from("123456780".to_string()),
+            account_type: Option::from("VIRTUAL_ACCOUNT".to_string()),
+            active: Option::from(true),
+            closed: Option::from(false),
+            ach_debit_enabled: Option::from(message.ach_debit_enabled.unwrap_or(false)),
+            ach_credit_enabled: Option::from(message.ach_credit_enabled.unwrap_or(false)),
+            statements_enabled: Option::from(message.statements_enabled.unwrap_or(false)),
+            created_epoch: Option::from(now_epoch()),
+            closed_epoch: Option::None,
+        });
+
+        success(
+            &reference,
+            "virtual account opened",
+            json!({ "virtual_account": entity.virtual_accounts.last() }),
+        )
+    }
+
+    fn get_virtual_account(&mut self, body: &[u8]) -> Reply {
+        let message: GetVirtualAccountMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        match entity
+            .virtual_accounts
+            .iter()
+            .find(|x| x.virtual_account_id.as_ref() == Some(&message.virtual_account_id))
+        {
+            Some(x) => success(&reference, "virtual account retrieved", json!({ "virtual_account": x })),
+            None => failure(StatusCode::OK, &reference, &format!("unknown virtual account {}", message.virtual_account_id)),
+        }
+    }
+
+    fn get_virtual_accounts(&mut self, header: &Header) -> Reply {
+        let entity = self.entity_mut(header);
+        success(
+            &header.reference,
+            "virtual accounts retrieved",
+            json!({ "virtual_accounts": entity.virtual_accounts }),
+        )
+    }
+
+    fn update_virtual_account(&mut self, body: &[u8]) -> Reply {
+        let message: UpdateVirtualAccountMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        let account = match entity
+            .virtual_accounts
+            .iter_mut()
+            .find(|x| x.virtual_account_id.as_ref() == Some(&message.virtual_account_id))
+        {
+            Some(x) if x.closed == Some(true) => return failure(StatusCode::OK, &reference, "virtual account is closed"),
+            Some(x) => x,
+            None => {
+                return failure(StatusCode::OK, &reference, &format!("unknown virtual account {}", message.virtual_account_id))
+            }
+        };
+
+        account.virtual_account_name = message.virtual_account_name.or(account.virtual_account_name.take());
+        account.active = message.active.or(account.active);
+        account.ach_debit_enabled = message.ach_debit_enabled.or(account.ach_debit_enabled);
+        account.ach_credit_enabled = message.ach_credit_enabled.or(account.ach_credit_enabled);
+        account.statements_enabled = message.statements_enabled.or(account.statements_enabled);
+
+        success(&reference, "virtual account updated", json!({ "virtual_account": account }))
+    }
+
+    fn close_virtual_account(&mut self, body: &[u8]) -> Reply {
+        let message: CloseVirtualAccountMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let entity = self.entity_mut(&message.header);
+
+        let account = match entity.virtual_accounts.iter_mut().find(|x| {
+            x.virtual_account_id.as_ref() == Some(&message.virtual_account_id)
+                && x.account_number.as_ref() == Some(&message.account_number)
+        }) {
+            Some(x) if x.closed == Some(true) => return failure(StatusCode::OK, &reference, "virtual account is already closed"),
+            Some(x) => x,
+            None => {
+                return failure(StatusCode::OK, &reference, &format!("unknown virtual account {}", message.virtual_account_id))
+            }
+        };
+
+        account.active = Option::from(false);
+        account.closed = Option::from(true);
+        account.closed_epoch = Option::from(now_epoch());
+
+        success(&reference, "virtual account closed", json!({ "virtual_account": account }))
+    }
+
+    fn get_sila_balance(&self, body: &[u8]) -> Reply {
+        let message: GetSilaBalanceMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+
+        let address = match (&message.address, &message.header.user_handle) {
+            (Some(x), _) => match x.parse::<H160>() {
+                Ok(x) => x,
+                Err(_) => return failure(StatusCode::BAD_REQUEST, &reference, "invalid address"),
+            },
+            (None, Some(x)) => match self.entities.get(x) {
+                Some(x) => x.address,
+                None => return failure(StatusCode::BAD_REQUEST, &reference, "unknown user handle"),
+            },
+            (None, None) => return failure(StatusCode::BAD_REQUEST, &reference, "address is required"),
+        };
+
+        let balance = self
+            .entities
+            .values()
+            .find(|x| x.address == address)
+            .map(|x| x.balance)
+            .unwrap_or(SilaAmount::ZERO);
+
+        success(
+            &reference,
+            "balance retrieved",
+            json!({
+                "address": format!("{:#x}", address),
+                "sila_balance": balance,
+                "response_time_ms": "0",
+            }),
+        )
+    }
+
+    fn require_kyc(&self, handle: &str, reference: &str) -> Option<Reply> {
+        match self.entities.get(handle).and_then(|x| x.kyc_status()) {
+            Some(MockKycStatus::Passed) => Option::None,
+            _ => Option::from(failure(StatusCode::OK, reference, &format!("{} has not passed KYC", handle))),
+        }
+    }
+
+    fn require_funding(&self, handle: &str, account_name: &str, card_name: Option<&str>, reference: &str) -> Option<Reply> {
+        let entity = &self.entities[handle];
+
+        match card_name {
+            Some(x) if !entity.cards.iter().any(|c| c.card_name.as_deref() == Some(x)) => {
+                Option::from(failure(StatusCode::OK, reference, &format!("no linked card named {}", x)))
+            }
+            Some(_) => Option::None,
+            None if !entity.accounts.contains(account_name) => {
+                Option::from(failure(StatusCode::OK, reference, &format!("no linked account named {}", account_name)))
+            }
+            None => Option::None,
+        }
+    }
+
+    fn submit(
+        &mut self,
+        header: &Header,
+        transaction_type: TransactionType,
+        amount: SilaAmount,
+        destination_handle: Option<String>,
+        bank_account_name: Option<String>,
+        descriptor: Option<String>,
+    ) -> Reply {
+        let handle = header.user_handle.clone().unwrap_or_default();
+        let debited = !matches!(transaction_type, TransactionType::Issue);
+
+        if debited {
+            let entity = self.entities.get_mut(&handle).expect("handle was validated on dispatch");
+
+            match entity.balance.checked_sub(amount) {
+                Some(x) => entity.balance = x,
+                None => return failure(StatusCode::OK, &header.reference, "insufficient SILA balance"),
+            }
+        }
+
+        let mut remaining: VecDeque<TransactionStatus> = self.transaction_script.iter().cloned().collect();
+        let status = remaining.pop_front().unwrap_or(TransactionStatus::Queued);
+        let transaction_id = Uuid::new_v4().to_string();
+        let now = now_epoch();
+
+        self.transactions.push(MockTransaction {
+            transaction_id: transaction_id.clone(),
+            reference_id: header.reference.clone(),
+            user_handle: handle,
+            transaction_type: transaction_type.clone(),
+            amount,
+            status,
+            remaining,
+            destination_handle,
+            bank_account_name,
+            descriptor: descriptor.clone(),
+            created_epoch: now,
+            last_update_epoch: now,
+            debited,
+            settled: false,
+        });
+
+        let index = self.transactions.len() - 1;
+        self.apply(index);
+
+        success(
+            &header.reference,
+            &format!("{} process started", transaction_type),
+            json!({ "transaction_id": transaction_id, "descriptor": descriptor }),
+        )
+    }
+
+    fn issue_sila(&mut self, body: &[u8]) -> Reply {
+        let message: IssueSilaMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let handle = message.header.user_handle.clone().unwrap_or_default();
+
+        if let Some(x) = self.require_kyc(&handle, &reference) {
+            return x;
+        }
+
+        let account_name = message.account_name.clone().unwrap_or_else(|| "default".to_string());
+
+        if let Some(x) = self.require_funding(&handle, &account_name, message.card_name.as_deref(), &reference) {
+            return x;
+        }
+
+        self.submit(
+            &message.header,
+            TransactionType::Issue,
+            message.amount,
+            Option::None,
+            Option::from(account_name),
+            message.descriptor,
+        )
+    }
+
+    fn redeem_sila(&mut self, body: &[u8]) -> Reply {
+        let message: RedeemSilaMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let handle = message.header.user_handle.clone().unwrap_or_default();
+
+        if let Some(x) = self.require_kyc(&handle, &reference) {
+            return x;
+        }
+
+        let account_name = message.account_name.clone().unwrap_or_else(|| "default".to_string());
+
+        if let Some(x) = self.require_funding(&handle, &account_name, message.card_name.as_deref(), &reference) {
+            return x;
+        }
+
+        self.submit(
+            &message.header,
+            TransactionType::Redeem,
+            message.amount,
+            Option::None,
+            Option::from(account_name),
+            message.descriptor,
+        )
+    }
+
+    fn transfer_sila(&mut self, body: &[u8]) -> Reply {
+        let message: TransferSilaMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let handle = message.header.user_handle.clone().unwrap_or_default();
+
+        if let Some(x) = self.require_kyc(&handle, &reference) {
+            return x;
+        }
+
+        if !self.entities.contains_key(&message.destination_handle) {
+            return failure(
+                StatusCode::OK,
+                &reference,
+                &format!("unknown destination handle {}", message.destination_handle),
+            );
+        }
+
+        self.submit(
+            &message.header,
+            TransactionType::Transfer,
+            message.amount,
+            Option::from(message.destination_handle.clone()),
+            Option::None,
+            message.descriptor,
+        )
+    }
+
+    fn cancel_transaction(&mut self, body: &[u8]) -> Reply {
+        let message: CancelTransactionMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+        let handle = message.header.user_handle.clone().unwrap_or_default();
+
+        let index = self
+            .transactions
+            .iter()
+            .position(|x| x.transaction_id == message.transaction_id && x.user_handle == handle);
+
+        match index {
+            Some(i) if matches!(self.transactions[i].status, TransactionStatus::Queued | TransactionStatus::Pending) => {
+                let transaction = &mut self.transactions[i];
+                transaction.status = TransactionStatus::Failed;
+                transaction.remaining.clear();
+                transaction.last_update_epoch = now_epoch();
+                self.apply(i);
+
+                success(&reference, "transaction cancelled", json!({}))
+            }
+            Some(_) => failure(StatusCode::OK, &reference, "transaction can no longer be cancelled"),
+            None => failure(StatusCode::OK, &reference, "unknown transaction_id"),
+        }
+    }
+
+    fn get_transactions(&mut self, body: &[u8]) -> Reply {
+        let message: GetTransactionsMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+
+        self.advance();
+
+        let filters = message.search_filters.unwrap_or_default();
+        let handle = message.header.user_handle.clone();
+
+        let mut matching: Vec<&MockTransaction> = self
+            .transactions
+            .iter()
+            .filter(|x| handle.as_ref().is_none_or(|h| &x.user_handle == h))
+            .filter(|x| filters.transaction_id.as_ref().is_none_or(|id| &x.transaction_id == id))
+            .filter(|x| filters.reference_id.as_ref().is_none_or(|id| &x.reference_id == id))
+            .filter(|x| filters.statuses.as_ref().is_none_or(|s| s.contains(&x.status)))
+            .filter(|x| filters.min_sila_amount.is_none_or(|a| x.amount >= a))
+            .filter(|x| filters.max_sila_amount.is_none_or(|a| x.amount <= a))
+            .collect();
+
+        if !filters.sort_ascending.unwrap_or(false) {
+            matching.reverse();
+        }
+
+        let (transactions, pagination) = paginate_json(
+            matching.into_iter().map(|x| x.to_json()).collect(),
+            filters.page.unwrap_or(1).max(1) as usize,
+            filters.per_page.unwrap_or(20).max(1) as usize,
+        );
+
+        success(
+            &reference,
+            "transactions retrieved",
+            json!({
+                "page": pagination["current_page"],
+                "returned_count": pagination["returned_count"],
+                "total_count": pagination["total_count"],
+                "pagination": pagination,
+                "transactions": transactions,
+            }),
+        )
+    }
+
+    // Approving leaves the transaction on its script; rejecting fails it like a cancel.
+    fn approve_wire(&mut self, body: &[u8]) -> Reply {
+        let message: ApproveWireMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+
+        let index = self
+            .transactions
+            .iter()
+            .position(|x| x.transaction_id == message.transaction_id);
+
+        let wire_status = match index {
+            Some(i) if matches!(self.transactions[i].status, TransactionStatus::Queued | TransactionStatus::Pending) => {
+                if !message.approve {
+                    let transaction = &mut self.transactions[i];
+                    transaction.status = TransactionStatus::Failed;
+                    transaction.remaining.clear();
+                    transaction.last_update_epoch = now_epoch();
+                    self.apply(i);
+                }
+
+                if message.approve { "approved" } else { "rejected" }
+            }
+            Some(_) => return failure(StatusCode::OK, &reference, "wire is no longer pending approval"),
+            None => return failure(StatusCode::OK, &reference, "unknown transaction_id"),
+        };
+
+        success(
+            &reference,
+            &format!("wire {}", wire_status),
+            json!({ "transaction_id": message.transaction_id, "wire_status": wire_status }),
+        )
+    }
+
+    fn get_webhooks(&self, body: &[u8]) -> Reply {
+        let message: GetWebhooksMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+
+        let filters = message.search_filters.unwrap_or_default();
+        let handle = filters.user_handle.as_ref().or(message.header.user_handle.as_ref());
+
+        let mut matching: Vec<Value> = self
+            .webhooks
+            .iter()
+            .filter(|x| handle.is_none_or(|h| x.user_handle.as_ref() == Some(h)))
+            .filter(|x| filters.uuid.is_none() || x.uuid == filters.uuid)
+            .filter(|x| filters.delivered.is_none() || x.delivered == filters.delivered)
+            .filter(|x| filters.event_type.is_none() || x.event_type == filters.event_type)
+            .filter(|x| filters.endpoint_name.is_none() || x.endpoint_name == filters.endpoint_name)
+            .filter(|x| filters.start_epoch.is_none_or(|e| x.created_epoch.unwrap_or_default() >= e))
+            .filter(|x| filters.end_epoch.is_none_or(|e| x.created_epoch.unwrap_or_default() <= e))
+            .map(|x| json!(x))
+            .collect();
+
+        if !filters.sort_ascending.unwrap_or(false) {
+            matching.reverse();
+        }
+
+        let (webhooks, pagination) = paginate_json(
+            matching,
+            filters.page.unwrap_or(1).max(1) as usize,
+            filters.per_page.unwrap_or(20).max(1) as usize,
+        );
+
+        success(
+            &reference,
+            "webhooks retrieved",
+            json!({ "webhooks": webhooks, "pagination": pagination }),
+        )
+    }
+
+    fn retry_webhook(&mut self, body: &[u8]) -> Reply {
+        let message: RetryWebhookMessage = match serde_json::from_slice(body) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+
+        match self.webhooks.iter_mut().find(|x| x.uuid.as_ref() == Some(&message.event_uuid)) {
+            Some(x) => {
+                x.attempts = Option::from(x.attempts.unwrap_or_default() + 1);
+                x.delivered = Option::from(true);
+                x.last_attempt_epoch = Option::from(now_epoch());
+                success(&reference, "webhook queued for retry", json!({}))
+            }
+            None => failure(StatusCode::OK, &reference, &format!("unknown event {}", message.event_uuid)),
+        }
+    }
+
+    // Each file part must match the hash signed for it in the data part.
+    fn upload_document(&mut self, body: &[u8], files: &[(String, Vec<u8>)]) -> Reply {
+        let value: Value = serde_json::from_slice(body).unwrap_or_default();
+        let message: DocumentMessage = match serde_json::from_value(value.clone()) {
+            Ok(x) => x,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, "", &e.to_string()),
+        };
+        let reference = message.header.reference.clone();
+
+        if files.is_empty() {
+            return failure(StatusCode::BAD_REQUEST, &reference, "a document needs a file part");
+        }
+
+        for (name, data) in files {
+            if signed_hash(&value, name) != Some(sha256_hex(data).as_str()) {
+                return failure(StatusCode::BAD_REQUEST, &reference, &format!("{} does not match its signed hash", name));
+            }
+        }
+
+        let document_id = Uuid::new_v4().to_string();
+        self.entity_mut(&message.header).documents.push(document_id.clone());
+
+        success(
+            &reference,
+            "File uploaded successfully.",
+            json!({"reference_id": Uuid::new_v4().to_string(), "document_id": document_id}),
+        )
+    }
+
+    fn advance(&mut self) {
+        for i in 0..self.transactions.len() {
+            if let Some(status) = self.transactions[i].remaining.pop_front() {
+                self.transactions[i].status = status;
+                self.transactions[i].last_update_epoch = now_epoch();
+                self.apply(i);
+            }
+        }
+    }
+
+    // Queues a transaction webhook for the new status, and moves balances once a transaction
+    // settles or fails.
+    fn apply(&mut self, index: usize) {
+        let transaction = &self.transactions[index];
+        let handle = transaction.user_handle.clone();
+        let payload = json!({ "transaction_id": transaction.transaction_id, "status": transaction.status });
+        self.notify("transaction", &handle, payload);
+
+        let transaction = &self.transactions[index];
+
+        if transaction.settled {
+            return;
+        }
+
+        let (credit, settled) = match transaction.status {
+            TransactionStatus::Success => match transaction.transaction_type {
+                TransactionType::Issue => (Option::from(transaction.user_handle.clone()), true),
+                TransactionType::Transfer => (transaction.destination_handle.clone(), true),
+                TransactionType::Redeem | TransactionType::Unknown => (Option::None, true),
+            },
+            TransactionStatus::Failed | TransactionStatus::Reversed | TransactionStatus::Rollback
+                if transaction.debited =>
+            {
+                (Option::from(transaction.user_handle.clone()), true)
+            }
+            TransactionStatus::Failed | TransactionStatus::Reversed | TransactionStatus::Rollback => {
+                (Option::None, true)
+            }
+            _ => (Option::None, false),
+        };
+
+        let amount = transaction.amount;
+
+        if let Some(entity) = credit.and_then(|x| self.entities.get_mut(&x)) {
+            match entity.balance.checked_add(amount) {
+                Some(x) => entity.balance = x,
+                None => error!("mock gateway balance overflow"),
+            }
+        }
+
+        self.transactions[index].settled = settled;
+    }
+}
+
+// Serves on an ephemeral localhost port from a dedicated thread, so the server outlives any
+// single test runtime. Sending on the returned channel shuts it down.
+pub(crate) fn spawn_server<H, Fut>(
+    name: &'static str,
+    handler: H,
+) -> Result<(SocketAddr, oneshot::Sender<()>), Box<dyn std::error::Error + Sync + Send>>
+where
+    H: Fn(Request<Body>) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Result<Response<Body>, Infallible>> + Send + 'static,
+{
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    listener.set_nonblocking(true)?;
+    let address = listener.local_addr()?;
+
+    let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    std::thread::spawn(move || {
+        runtime.block_on(async move {
+            let make_service = make_service_fn(move |_| {
+                let handler = handler.clone();
+                async move { Ok::<_, Infallible>(service_fn(handler)) }
+            });
+
+            let server = match Server::from_tcp(listener) {
+                Ok(x) => x,
+                Err(e) => {
+                    error!("{} failed to start: {}", name, e);
+                    return;
+                }
+            };
+
+            let result = server
+                .serve(make_service)
+                .with_graceful_shutdown(async {
+                    shutdown_rx.await.ok();
+                })
+                .await;
+
+            if let Err(e) = result {
+                error!("{} error: {}", name, e);
+            }
+        })
+    });
+
+    Ok((address, shutdown))
+}
+
+pub struct MockGateway {
+    address: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockGateway {
+    pub fn start(app_handle: &str, app_address: H160) -> Result<MockGateway, Box<dyn std::error::Error + Sync + Send>> {
+        let state = Arc::new(Mutex::new(MockState::new(app_handle.to_string(), app_address)));
+
+        let server_state = state.clone();
+        let (address, shutdown) = spawn_server("mock gateway", move |req| handle(server_state.clone(), req))?;
+
+        Ok(MockGateway {
+            address,
+            state,
+            shutdown: Option::from(shutdown),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/{}", self.address, VERSION)
+    }
+
+    // Statuses checked by successive check_kyc calls after request_kyc; the last one sticks.
+    pub fn set_kyc_script(&self, script: Vec<MockKycStatus>) {
+        self.state.lock().unwrap().kyc_script = script;
+    }
+
+    // Statuses new transactions move through, one per get_transactions call or advance().
+    pub fn set_transaction_script(&self, script: Vec<TransactionStatus>) {
+        self.state.lock().unwrap().transaction_script = script;
+    }
+
+    pub fn advance(&self) {
+        self.state.lock().unwrap().advance();
+    }
+
+    pub fn is_registered(&self, handle: &str) -> bool {
+        self.state.lock().unwrap().entities.contains_key(handle)
+    }
+
+    pub fn balance(&self, handle: &str) -> Option<SilaAmount> {
+        self.state.lock().unwrap().entities.get(handle).map(|x| x.balance)
+    }
+
+    // The code the last request_sms_confirmation for the phone would have texted.
+    pub fn sms_code(&self, handle: &str, phone_uuid: &str) -> Option<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .entities
+            .get(handle)
+            .and_then(|x| x.sms_codes.get(phone_uuid).cloned())
+    }
+
+    // the document_id of each document uploaded for the handle
+    pub fn documents(&self, handle: &str) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .entities
+            .get(handle)
+            .map(|x| x.documents.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn transaction_status(&self, transaction_id: &str) -> Option<TransactionStatus> {
+        self.state
+            .lock()
+            .unwrap()
+            .transactions
+            .iter()
+            .find(|x| x.transaction_id == transaction_id)
+            .map(|x| x.status.clone())
+    }
+}
+
+impl Drop for MockGateway {
+    fn drop(&mut self) {
+        if let Some(x) = self.shutdown.take() {
+            x.send(()).ok();
+        }
+    }
+}
+
+async fn handle(state: Arc<Mutex<MockState>>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    // the endpoint is the whole path after the version, e.g. update/phone
+    let path = req.uri().path();
+    let endpoint = path
+        .strip_prefix(&format!("/{}/", VERSION))
+        .unwrap_or(path)
+        .to_string();
+    let query = req.uri().query().unwrap_or_default().to_string();
+
+    let authsignature = req
+        .headers()
+        .get("authsignature")
+        .and_then(|x| x.to_str().ok())
+        .map(|x| x.to_string());
+    let usersignature = req
+        .headers()
+        .get("usersignature")
+        .and_then(|x| x.to_str().ok())
+        .map(|x| x.to_string());
+
+    let content_type = req
+        .headers()
+        .get("content-type")
+        .and_then(|x| x.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    // a multipart body is signed through its "data" part; the rest are files
+    let parts = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) if content_type.starts_with("multipart/form-data") => {
+            match multipart_parts(&content_type, &body) {
+                Some(mut parts) => match parts.iter().position(|(name, _)| name == "data") {
+                    Some(i) => Ok((parts.remove(i).1, parts)),
+                    None => Err("a multipart body needs a data part".to_string()),
+                },
+                None => Err("invalid multipart body".to_string()),
+            }
+        }
+        Ok(body) => Ok((body.to_vec(), Vec::new())),
+        Err(e) => Err(e.to_string()),
+    };
+
+    let (code, body) = match parts {
+        Ok((body, files)) => state.lock().unwrap().dispatch(
+            &endpoint,
+            &query,
+            &body,
+            &files,
+            authsignature.as_deref(),
+            usersignature.as_deref(),
+        ),
+        Err(e) => failure(StatusCode::BAD_REQUEST, "", &e),
+    };
+
+    let response = Response::builder()
+        .status(code)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap_or_else(|_| Response::new(Body::empty()));
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_endpoint_is_routed() {
+        let paths = [
+            CheckHandle::PATH,
+            Register::PATH,
+            GetEntity::PATH,
+            GetEntities::PATH,
+            RequestKyc::PATH,
+            CheckKyc::PATH,
+            CheckPartnerKyc::PATH,
+            UpdateAddress::PATH,
+            UpdateEmail::PATH,
+            UpdateIdentity::PATH,
+            UpdatePhone::PATH,
+            AddPhone::PATH,
+            RequestSmsConfirmation::PATH,
+            ConfirmSms::PATH,
+            LinkAccount::PATH,
+            CheckInstantAch::PATH,
+            GetPaymentMethods::PATH,
+            LinkCard::PATH,
+            GetCards::PATH,
+            DeleteCard::PATH,
+            OpenVirtualAccount::PATH,
+            GetVirtualAccount::PATH,
+            GetVirtualAccounts::PATH,
+            UpdateVirtualAccount::PATH,
+            CloseVirtualAccount::PATH,
+            GetSilaBalance::PATH,
+            IssueSila::PATH,
+            RedeemSila::PATH,
+            TransferSila::PATH,
+            CancelTransaction::PATH,
+            GetTransactions::PATH,
+            ApproveWire::PATH,
+            GetWebhooks::PATH,
+            RetryWebhook::PATH,
+            UploadDocument::PATH,
+        ];
+
+        let unrouted: Vec<&str> = paths.iter().copied().filter(|x| find_route(x).is_none()).collect();
+        assert!(unrouted.is_empty(), "not handled by the mock gateway: {:?}", unrouted);
+
+        let routed: HashSet<&str> = routes().iter().map(|x| x.path).collect();
+        assert_eq!(routed.len(), routes().len(), "a path is routed twice");
+        assert_eq!(routed.len(), paths.len());
+    }
+
+    #[test]
+    fn reads_paging_from_the_query_string() {
+        assert_eq!(query_param("page=2&per_page=5", "per_page"), Option::from(5));
+        assert_eq!(query_param("page=2", "per_page"), Option::None);
+        assert_eq!(query_param("", "page"), Option::None);
+
+        let items: Vec<Value> = (0..5).map(|x| json!(x)).collect();
+        let (page, pagination) = paginate_json(items, 3, 2);
+        assert_eq!(page, vec![json!(4)]);
+        assert_eq!(pagination["total_pages"], json!(3));
+        assert_eq!(pagination["returned_count"], json!(1));
+    }
+
+    #[test]
+    fn reads_multipart_parts_and_checks_document_hashes() {
+        let body = b"--xyz\r\nContent-Disposition: form-data; name=\"data\"\r\n\r\n{}\r\n--xyz\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"front.png\"\r\n\
+            Content-Type: image/png\r\n\r\nfront\r\n--xyz--\r\n";
+
+        let parts = multipart_parts("multipart/form-data; boundary=xyz", body).unwrap();
+        assert_eq!(
+            parts,
+            vec![("data".to_string(), b"{}".to_vec()), ("file".to_string(), b"front".to_vec())]
+        );
+        assert!(multipart_parts("multipart/form-data; boundary=abc", body).is_none());
+
+        let mut state = MockState::new("app".to_string(), H160::zero());
+        let message = json!({
+            "header": Header::default(),
+            "filename": "front.png",
+            "mime_type": "image/png",
+            "document_type": "id_drivers_license",
+            "hash": sha256_hex(b"back"),
+        });
+        let body = serde_json::to_vec(&message).unwrap();
+
+        let (code, reply) = state.upload_document(&body, &[("file".to_string(), b"front".to_vec())]);
+        assert_eq!(code, StatusCode::BAD_REQUEST);
+        assert_eq!(reply["message"], "file does not match its signed hash");
+
+        let (code, _) = state.upload_document(&body, &[]);
+        assert_eq!(code, StatusCode::BAD_REQUEST);
+    }
+}
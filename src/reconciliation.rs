@@ -0,0 +1,277 @@
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+
+use crate::{
+    get_transactions_stream, GetTransactionsMessageParams, Signatures, SilaAmount, Transaction,
+    TransactionSearchFilters, TransactionStatus, TransactionType,
+};
+
+// One issue, redeem or transfer as recorded on our side. reference is the value we set as the
+// header reference when the transaction was submitted, which Sila reports back as reference_id.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct LedgerEntry {
+    pub reference: String,
+    pub transaction_id: Option<String>,
+    pub transaction_type: TransactionType,
+    pub sila_amount: SilaAmount,
+    pub status: Option<TransactionStatus>,
+    pub user_handle: Option<String>,
+    pub created_epoch: Option<i64>,
+}
+
+pub trait Ledger {
+    fn entries(
+        &self,
+        start_epoch: i64,
+        end_epoch: i64,
+    ) -> impl Future<Output = Result<Vec<LedgerEntry>, Box<dyn std::error::Error + Sync + Send>>> + Send;
+}
+
+#[derive(Clone, Default)]
+pub struct InMemoryLedger {
+    entries: Vec<LedgerEntry>,
+}
+
+impl InMemoryLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, entry: LedgerEntry) {
+        self.entries.push(entry);
+    }
+}
+
+impl Ledger for InMemoryLedger {
+    // entries without a created_epoch can't be placed in a window, so they are always included
+    fn entries(
+        &self,
+        start_epoch: i64,
+        end_epoch: i64,
+    ) -> impl Future<Output = Result<Vec<LedgerEntry>, Box<dyn std::error::Error + Sync + Send>>> + Send {
+        let entries = self
+            .entries
+            .iter()
+            .filter(|x| {
+                x.created_epoch
+                    .map(|e| e >= start_epoch && e <= end_epoch)
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+
+        async move { Ok(entries) }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct AmountMismatch {
+    pub reference: String,
+    pub transaction_id: Option<String>,
+    pub ledger_amount: SilaAmount,
+    pub sila_amount: Option<SilaAmount>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct StatusDrift {
+    pub reference: String,
+    pub transaction_id: Option<String>,
+    pub ledger_status: Option<TransactionStatus>,
+    pub sila_status: Option<TransactionStatus>,
+}
+
+#[derive(Serialize, Default)]
+pub struct ReconciliationReport {
+    pub matched: usize,
+    pub missing: Vec<LedgerEntry>,
+    pub extra: Vec<Transaction>,
+    pub amount_mismatches: Vec<AmountMismatch>,
+    pub status_drift: Vec<StatusDrift>,
+}
+
+impl ReconciliationReport {
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty()
+            && self.extra.is_empty()
+            && self.amount_mismatches.is_empty()
+            && self.status_drift.is_empty()
+    }
+}
+
+impl std::fmt::Display for ReconciliationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReconciliationReport(matched: {}, missing: {}, extra: {}, amount_mismatches: {}, status_drift: {})",
+            self.matched,
+            self.missing.len(),
+            self.extra.len(),
+            self.amount_mismatches.len(),
+            self.status_drift.len()
+        )
+    }
+}
+
+// Matches on reference_id first and falls back to transaction_id for entries recorded without
+// a reference Sila knows about.
+pub fn diff(entries: Vec<LedgerEntry>, transactions: Vec<Transaction>) -> ReconciliationReport {
+    let mut report = ReconciliationReport::default();
+
+    let mut by_reference: HashMap<String, usize> = HashMap::new();
+    let mut by_transaction_id: HashMap<String, usize> = HashMap::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        by_reference.insert(entry.reference.clone(), i);
+        if let Some(x) = &entry.transaction_id {
+            by_transaction_id.insert(x.clone(), i);
+        }
+    }
+
+    let mut matched = vec![false; entries.len()];
+
+    for transaction in transactions {
+        let index = transaction
+            .reference_id
+            .as_ref()
+            .and_then(|x| by_reference.get(x))
+            .or_else(|| {
+                transaction
+                    .transaction_id
+                    .as_ref()
+                    .and_then(|x| by_transaction_id.get(x))
+            })
+            .copied()
+            .filter(|i| !matched[*i]);
+
+        let i = match index {
+            Some(i) => i,
+            None => {
+                report.extra.push(transaction);
+                continue;
+            }
+        };
+
+        matched[i] = true;
+        report.matched += 1;
+
+        let entry = &entries[i];
+        let transaction_id = transaction
+            .transaction_id
+            .clone()
+            .or_else(|| entry.transaction_id.clone());
+
+        if transaction.sila_amount != Some(entry.sila_amount) {
+            report.amount_mismatches.push(AmountMismatch {
+                reference: entry.reference.clone(),
+                transaction_id: transaction_id.clone(),
+                ledger_amount: entry.sila_amount,
+                sila_amount: transaction.sila_amount,
+            });
+        }
+
+        if entry.status.is_some() && entry.status != transaction.status {
+            report.status_drift.push(StatusDrift {
+                reference: entry.reference.clone(),
+                transaction_id,
+                ledger_status: entry.status.clone(),
+                sila_status: transaction.status.clone(),
+            });
+        }
+    }
+
+    report.missing = entries
+        .into_iter()
+        .zip(matched)
+        .filter(|(_, m)| !m)
+        .map(|(x, _)| x)
+        .collect();
+
+    report
+}
+
+pub async fn reconcile<L, F, Fut>(
+    ledger: &L,
+    sila_handle: Option<String>,
+    start_epoch: i64,
+    end_epoch: i64,
+    sign: F,
+) -> Result<ReconciliationReport, Box<dyn std::error::Error + Sync + Send>>
+where
+    L: Ledger,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Signatures>,
+{
+    let entries = ledger.entries(start_epoch, end_epoch).await?;
+
+    let params = GetTransactionsMessageParams {
+        sila_handle,
+        reference: Option::None,
+        search_filters: Option::from(TransactionSearchFilters {
+            start_epoch: Option::from(start_epoch),
+            end_epoch: Option::from(end_epoch),
+            show_timelines: Option::from(false),
+            per_page: Option::from(100),
+            ..Default::default()
+        }),
+    };
+
+    let transactions: Vec<Transaction> = get_transactions_stream(params, sign).try_collect().await?;
+
+    Ok(diff(entries, transactions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(reference: &str, amount: u32, status: TransactionStatus) -> LedgerEntry {
+        LedgerEntry {
+            reference: reference.to_string(),
+            transaction_id: Option::None,
+            transaction_type: TransactionType::Issue,
+            sila_amount: SilaAmount::from(amount),
+            status: Option::from(status),
+            user_handle: Option::None,
+            created_epoch: Option::None,
+        }
+    }
+
+    fn transaction(reference: &str, amount: u32, status: &str) -> Transaction {
+        serde_json::from_value(serde_json::json!({
+            "reference_id": reference,
+            "transaction_id": format!("tx-{}", reference),
+            "transaction_type": "issue",
+            "sila_amount": amount,
+            "status": status,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn reports_each_kind_of_difference() {
+        let entries = vec![
+            entry("a", 100, TransactionStatus::Success),
+            entry("b", 200, TransactionStatus::Success),
+            entry("c", 300, TransactionStatus::Pending),
+            entry("d", 400, TransactionStatus::Pending),
+        ];
+        let transactions = vec![
+            transaction("a", 100, "success"),
+            transaction("b", 250, "success"),
+            transaction("c", 300, "failed"),
+            transaction("e", 500, "success"),
+        ];
+
+        let report = diff(entries, transactions);
+
+        assert_eq!(report.matched, 3);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].reference, "d");
+        assert_eq!(report.extra.len(), 1);
+        assert_eq!(report.amount_mismatches.len(), 1);
+        assert_eq!(report.amount_mismatches[0].reference, "b");
+        assert_eq!(report.status_drift.len(), 1);
+        assert_eq!(report.status_drift[0].reference, "c");
+        assert!(!report.is_clean());
+    }
+}
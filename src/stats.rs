@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use metrics::{counter, describe_counter, describe_histogram, histogram, Unit};
+
+// Metrics for every call made through execute, recorded through the metrics facade so they go
+// to whichever recorder the application installs. Every series carries an endpoint label with
+// the endpoint path, e.g. issue_sila or update/phone. With the prometheus feature,
+// install_prometheus installs a recorder and returns a handle that renders the text format.
+
+pub const REQUESTS: &str = "sila_requests_total";
+// labelled with status: the HTTP status code, or "transport" when no response was received
+pub const HTTP_ERRORS: &str = "sila_http_errors_total";
+pub const DECODE_ERRORS: &str = "sila_decode_errors_total";
+// responses Sila marked as failed, e.g. "status": "FAILURE"
+pub const FAILURES: &str = "sila_failures_total";
+pub const RETRIES: &str = "sila_retries_total";
+pub const REQUEST_DURATION: &str = "sila_request_duration_seconds";
+// Sila's own response_time_ms, so gateway time can be told apart from network and client time
+pub const SILA_RESPONSE_TIME: &str = "sila_response_time_seconds";
+
+pub fn describe() {
+    describe_counter!(REQUESTS, "Calls made to the Sila API");
+    describe_counter!(HTTP_ERRORS, "Sila API calls that returned an HTTP error or no response");
+    describe_counter!(DECODE_ERRORS, "Sila API responses that could not be decoded");
+    describe_counter!(FAILURES, "Sila API responses with a FAILURE status");
+    describe_counter!(RETRIES, "Sila API calls retried by the caller");
+    describe_histogram!(REQUEST_DURATION, Unit::Seconds, "Sila API call latency as seen by the client");
+    describe_histogram!(SILA_RESPONSE_TIME, Unit::Seconds, "Sila API processing time reported by Sila");
+}
+
+// The crate never resends a request itself; callers that retry a call record it here.
+pub fn record_retry(endpoint: &'static str) {
+    counter!(RETRIES, "endpoint" => endpoint).increment(1);
+}
+
+pub(crate) fn record_request(endpoint: &'static str) {
+    counter!(REQUESTS, "endpoint" => endpoint).increment(1);
+}
+
+pub(crate) fn record_transport_error(endpoint: &'static str) {
+    counter!(HTTP_ERRORS, "endpoint" => endpoint, "status" => "transport").increment(1);
+}
+
+pub(crate) fn record_reply(endpoint: &'static str, status: u16, body: &str) {
+    if status >= 400 {
+        counter!(HTTP_ERRORS, "endpoint" => endpoint, "status" => status.to_string()).increment(1);
+    }
+
+    // response_time_ms is usually a string, but accept a number too
+    let response_time = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|x| match &x["response_time_ms"] {
+            serde_json::Value::String(x) => x.parse::<f64>().ok(),
+            x => x.as_f64(),
+        });
+
+    if let Some(x) = response_time {
+        histogram!(SILA_RESPONSE_TIME, "endpoint" => endpoint).record(x / 1000.0);
+    }
+}
+
+pub(crate) fn record_decode_error(endpoint: &'static str) {
+    counter!(DECODE_ERRORS, "endpoint" => endpoint).increment(1);
+}
+
+pub(crate) fn record_outcome(endpoint: &'static str, latency: Duration, failed: bool) {
+    histogram!(REQUEST_DURATION, "endpoint" => endpoint).record(latency.as_secs_f64());
+
+    if failed {
+        counter!(FAILURES, "endpoint" => endpoint).increment(1);
+    }
+}
+
+#[cfg(feature = "prometheus")]
+pub fn install_prometheus(
+) -> Result<metrics_exporter_prometheus::PrometheusHandle, Box<dyn std::error::Error + Sync + Send>> {
+    use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+
+    // buckets rather than summaries, so latencies can be aggregated across instances
+    let buckets = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Prefix("sila_".to_string()), &buckets)?
+        .install_recorder()?;
+
+    describe();
+    Ok(handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    #[test]
+    fn records_http_errors_and_sila_response_time() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            record_reply("issue_sila", 503, r#"{"status": "FAILURE", "response_time_ms": "250"}"#);
+            record_reply("issue_sila", 200, r#"{"status": "SUCCESS", "response_time_ms": 40}"#);
+            record_reply("issue_sila", 200, "not json");
+        });
+
+        let values: Vec<(String, Vec<String>, DebugValue)> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let labels = key.key().labels().map(|x| format!("{}={}", x.key(), x.value())).collect();
+                (key.key().name().to_string(), labels, value)
+            })
+            .collect();
+
+        assert!(values.iter().any(|(name, labels, value)| name == HTTP_ERRORS
+            && labels.contains(&"status=503".to_string())
+            && *value == DebugValue::Counter(1)));
+
+        let response_times = values
+            .iter()
+            .find(|(name, _, _)| name == SILA_RESPONSE_TIME)
+            .map(|(_, _, value)| value);
+        assert!(matches!(response_times, Some(DebugValue::Histogram(x)) if x.len() == 2));
+    }
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use tracing::field::Empty;
+use tracing::Span;
+
+// Every call made through execute runs inside a "sila_request" span. The span records the
+// endpoint, the user_handle and the header reference of the signed message, then the HTTP
+// status, Sila's status and reference from the reply and the latency. Message and response
+// bodies are never recorded, since they carry names, SSNs, addresses and account numbers.
+//
+// The otel.* fields are picked up by tracing-opentelemetry; with the otel feature, otel_layer
+// exports these spans so a reference can be followed into a distributed trace.
+
+pub(crate) fn request_span(path: &'static str, message: &serde_json::Value) -> Span {
+    tracing::info_span!(
+        "sila_request",
+        otel.name = path,
+        otel.kind = "client",
+        otel.status_code = Empty,
+        sila.endpoint = path,
+        sila.user_handle = message["header"]["user_handle"].as_str(),
+        sila.reference = message["header"]["reference"].as_str(),
+        sila.status = Empty,
+        sila.response_reference = Empty,
+        http.status_code = Empty,
+        latency_ms = Empty,
+        error = Empty,
+    )
+}
+
+pub(crate) fn record_reply(span: &Span, status: u16, body: &str) {
+    span.record("http.status_code", i64::from(status));
+
+    if let Ok(x) = serde_json::from_str::<serde_json::Value>(body) {
+        if let Some(status) = x["status"].as_str() {
+            span.record("sila.status", status);
+        }
+        if let Some(reference) = x["reference"].as_str() {
+            span.record("sila.response_reference", reference);
+        }
+    }
+}
+
+pub(crate) fn record_outcome(span: &Span, latency: Duration, failed: bool, error: Option<&str>) {
+    span.record("latency_ms", latency.as_millis() as i64);
+    span.record("otel.status_code", if failed || error.is_some() { "error" } else { "ok" });
+
+    if let Some(x) = error {
+        span.record("error", x);
+    }
+}
+
+#[cfg(feature = "otel")]
+pub fn otel_layer<S, T>(tracer: T) -> tracing_opentelemetry::OpenTelemetryLayer<S, T>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+    T: opentelemetry::trace::Tracer + tracing_opentelemetry::PreSampledTracer + 'static,
+{
+    // source locations and thread names add nothing to a client span
+    tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .with_location(false)
+        .with_threads(false)
+}
+
+// The OpenTelemetry trace id of the current span, for logging next to a Sila reference.
+#[cfg(feature = "otel")]
+pub fn current_trace_id() -> Option<String> {
+    use opentelemetry::trace::TraceContextExt;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let context = Span::current().context();
+    let span_context = context.span().span_context().clone();
+
+    match span_context.is_valid() {
+        true => Option::from(span_context.trace_id().to_string()),
+        false => Option::None,
+    }
+}
@@ -0,0 +1,323 @@
+use log::{error, warn};
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{
+    get_transactions, GetTransactionsMessage, GetTransactionsMessageParams, SignedMessageParams,
+    Signatures, Status, Transaction, TransactionSearchFilters, TransactionStatus,
+};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Transition {
+    Initial,
+    Unchanged,
+    Legal,
+    // permitted by Sila but worth a human look, e.g. success -> reversed after an ACH return
+    Anomalous,
+    Illegal,
+}
+
+#[derive(Clone)]
+pub struct StatusObservation {
+    pub status: TransactionStatus,
+    pub observed_at: Option<SystemTime>,
+}
+
+#[derive(Clone)]
+pub struct TransitionAnomaly {
+    pub from: TransactionStatus,
+    pub to: TransactionStatus,
+    pub transition: Transition,
+    pub observed_at: Option<SystemTime>,
+}
+
+impl std::fmt::Display for TransitionAnomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TransitionAnomaly({:?}: {} -> {})", self.transition, self.from, self.to)
+    }
+}
+
+pub struct TransactionTracker {
+    pub transaction_id: String,
+    history: Vec<StatusObservation>,
+    anomalies: Vec<TransitionAnomaly>,
+    // (status, date_epoch) of the timeline entries already replayed
+    seen_timeline: HashSet<(Option<String>, Option<i64>)>,
+}
+
+impl TransactionTracker {
+    pub fn new(transaction_id: &str) -> Self {
+        TransactionTracker {
+            transaction_id: transaction_id.to_string(),
+            history: Vec::new(),
+            anomalies: Vec::new(),
+            seen_timeline: HashSet::new(),
+        }
+    }
+
+    pub fn status(&self) -> Option<&TransactionStatus> {
+        self.history.last().map(|x| &x.status)
+    }
+
+    pub fn history(&self) -> &[StatusObservation] {
+        &self.history
+    }
+
+    pub fn anomalies(&self) -> &[TransitionAnomaly] {
+        &self.anomalies
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.status().map(|x| x.is_terminal()).unwrap_or(false)
+    }
+
+    // Sila remains the source of truth, so illegal transitions are recorded as anomalies but
+    // the tracked status still follows what was observed.
+    pub fn observe(
+        &mut self,
+        status: TransactionStatus,
+        observed_at: Option<SystemTime>,
+    ) -> Transition {
+        let transition = match self.status() {
+            None => Transition::Initial,
+            Some(x) if *x == status => Transition::Unchanged,
+            Some(x) if x.can_transition_to(&status) && *x == TransactionStatus::Success => {
+                Transition::Anomalous
+            }
+            Some(x) if x.can_transition_to(&status) => Transition::Legal,
+            Some(_) => Transition::Illegal,
+        };
+
+        if transition == Transition::Unchanged {
+            return transition;
+        }
+
+        if let Some(from) = self.status().cloned() {
+            if transition == Transition::Anomalous || transition == Transition::Illegal {
+                warn!(
+                    "transaction {} {:?} transition: {} -> {}",
+                    self.transaction_id, transition, from, status
+                );
+                self.anomalies.push(TransitionAnomaly {
+                    from,
+                    to: status.clone(),
+                    transition: transition.clone(),
+                    observed_at,
+                });
+            }
+        }
+
+        self.history.push(StatusObservation {
+            status,
+            observed_at,
+        });
+
+        transition
+    }
+
+    // Replays any timeline entries not seen before, then the transaction's current status. An
+    // entry can turn up on a later poll with the same second as one already replayed, so entries
+    // are told apart by status and epoch rather than by time alone.
+    pub fn observe_transaction(
+        &mut self,
+        transaction: &Transaction,
+    ) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        if transaction.transaction_id.as_deref() != Some(self.transaction_id.as_str()) {
+            return Err(Box::from(format!(
+                "transaction {} does not match tracked transaction {}",
+                transaction.transaction_id.clone().unwrap_or_default(),
+                self.transaction_id
+            )));
+        }
+
+        let mut timeline: Vec<_> = transaction
+            .timeline
+            .iter()
+            .flatten()
+            .filter(|x| !self.seen_timeline.contains(&(x.status.clone(), x.date_epoch)))
+            .collect();
+        timeline.sort_by_key(|x| x.date_epoch);
+
+        for item in timeline {
+            if let Some(status) = item.transaction_status() {
+                self.observe(status, item.timestamp());
+            }
+            self.seen_timeline.insert((item.status.clone(), item.date_epoch));
+        }
+
+        if let Some(status) = &transaction.status {
+            let observed_at = transaction
+                .last_update_epoch
+                .filter(|x| *x >= 0)
+                .map(|x| SystemTime::UNIX_EPOCH + Duration::from_secs(x as u64));
+            self.observe(status.clone(), observed_at);
+        }
+
+        Ok(())
+    }
+
+    pub async fn refresh<F, Fut>(
+        &mut self,
+        sila_handle: Option<String>,
+        sign: &F,
+    ) -> Result<Option<TransactionStatus>, Box<dyn std::error::Error + Sync + Send>>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Signatures>,
+    {
+        let params = GetTransactionsMessageParams {
+            sila_handle: sila_handle.clone(),
+            reference: Option::None,
+            search_filters: Option::from(TransactionSearchFilters {
+                transaction_id: Option::from(self.transaction_id.clone()),
+                ..Default::default()
+            }),
+        };
+
+        let message = serde_json::to_string(&GetTransactionsMessage::from(params))?;
+        let signatures = sign(message.clone()).await;
+
+        let response = get_transactions(&SignedMessageParams {
+            sila_handle,
+            message,
+            usersignature: signatures.usersignature,
+            authsignature: signatures.authsignature,
+        })
+        .await?;
+
+        if response.status == Status::FAILURE {
+            return Err(Box::from(format!(
+                "get_transactions failed for {}: {}",
+                self.transaction_id,
+                response.message.unwrap_or_default()
+            )));
+        }
+
+        for transaction in response.transactions.iter().flatten() {
+            if transaction.transaction_id.as_deref() == Some(self.transaction_id.as_str()) {
+                self.observe_transaction(transaction)?;
+            }
+        }
+
+        Ok(self.status().cloned())
+    }
+
+    pub async fn await_terminal<F, Fut>(
+        &mut self,
+        sila_handle: Option<String>,
+        timeout: Duration,
+        poll_interval: Duration,
+        sign: F,
+    ) -> Result<TransactionStatus, Box<dyn std::error::Error + Sync + Send>>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Signatures>,
+    {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(status) = self.status().filter(|x| x.is_terminal()) {
+                return Ok(status.clone());
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                error!(
+                    "timed out waiting for transaction {} to reach a terminal status",
+                    self.transaction_id
+                );
+                return Err(Box::from(format!(
+                    "timed out waiting for transaction {} (last status: {})",
+                    self.transaction_id,
+                    self.status().map(|x| x.to_string()).unwrap_or_else(|| "none".to_string())
+                )));
+            }
+
+            let refresh = self.refresh(sila_handle.clone(), &sign);
+
+            match tokio::time::timeout(deadline - now, refresh).await {
+                Ok(Ok(Some(status))) if status.is_terminal() => return Ok(status),
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => return Err(e),
+                Err(_) => continue,
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            tokio::time::sleep(poll_interval.min(remaining)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legal_progression_has_no_anomalies() {
+        let mut tracker = TransactionTracker::new("tx");
+
+        assert_eq!(tracker.observe(TransactionStatus::Queued, None), Transition::Initial);
+        assert_eq!(tracker.observe(TransactionStatus::Pending, None), Transition::Legal);
+        assert_eq!(tracker.observe(TransactionStatus::Pending, None), Transition::Unchanged);
+        assert_eq!(tracker.observe(TransactionStatus::Success, None), Transition::Legal);
+
+        assert!(tracker.is_terminal());
+        assert_eq!(tracker.history().len(), 3);
+        assert!(tracker.anomalies().is_empty());
+    }
+
+    #[test]
+    fn reversal_and_backwards_moves_are_flagged() {
+        let mut tracker = TransactionTracker::new("tx");
+
+        tracker.observe(TransactionStatus::Pending, None);
+        assert_eq!(tracker.observe(TransactionStatus::Queued, None), Transition::Illegal);
+        tracker.observe(TransactionStatus::Pending, None);
+        tracker.observe(TransactionStatus::Success, None);
+        assert_eq!(tracker.observe(TransactionStatus::Reversed, None), Transition::Anomalous);
+
+        assert_eq!(tracker.anomalies().len(), 2);
+        assert!(tracker.status() == Some(&TransactionStatus::Reversed));
+    }
+
+    #[test]
+    fn unknown_statuses_are_not_illegal_or_terminal() {
+        let mut tracker = TransactionTracker::new("tx");
+
+        tracker.observe(TransactionStatus::Pending, None);
+        assert_eq!(tracker.observe(TransactionStatus::Unknown, None), Transition::Legal);
+        assert!(!tracker.is_terminal());
+        assert_eq!(tracker.observe(TransactionStatus::Success, None), Transition::Legal);
+
+        assert!(tracker.anomalies().is_empty());
+    }
+
+    #[test]
+    fn timeline_entries_in_the_same_second_are_not_dropped() {
+        let transaction = |timeline: serde_json::Value| -> Transaction {
+            serde_json::from_value(serde_json::json!({"transaction_id": "tx", "timeline": timeline}))
+                .unwrap()
+        };
+
+        let mut tracker = TransactionTracker::new("tx");
+
+        tracker
+            .observe_transaction(&transaction(serde_json::json!([
+                {"date_epoch": 10, "status": "queued"},
+                {"date_epoch": 20, "status": "pending"},
+            ])))
+            .unwrap();
+
+        tracker
+            .observe_transaction(&transaction(serde_json::json!([
+                {"date_epoch": 10, "status": "queued"},
+                {"date_epoch": 20, "status": "pending"},
+                {"date_epoch": 20, "status": "success"},
+            ])))
+            .unwrap();
+
+        let history: Vec<String> = tracker.history().iter().map(|x| x.status.to_string()).collect();
+        assert_eq!(history, vec!["queued", "pending", "success"]);
+    }
+}
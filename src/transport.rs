@@ -0,0 +1,227 @@
+use log::error;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::endpoints::endpoint::{run, Payload};
+use crate::{SignedMessageParams, SilaEndpoint};
+
+// Sila's document endpoints sign only the JSON "data" part of a multipart body; each file is
+// bound to that signature by including its SHA-256 hash in the signed JSON.
+
+#[derive(Clone)]
+pub struct MultipartFile {
+    pub name: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl MultipartFile {
+    pub fn new(name: &str, filename: &str, mime_type: &str, data: Vec<u8>) -> Self {
+        MultipartFile {
+            name: name.to_string(),
+            filename: filename.to_string(),
+            mime_type: mime_type.to_string(),
+            data,
+        }
+    }
+
+    pub fn hash(&self) -> String {
+        sha256_hex(&self.data)
+    }
+}
+
+impl std::fmt::Display for MultipartFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MultipartFile (name: {}, filename: {}, mime_type: {}, hash: {})",
+            self.name,
+            self.filename,
+            self.mime_type,
+            self.hash())
+    }
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+// The hash Sila reads for a file part: file_metadata.<part name>.hash when the message carries
+// several files, otherwise the top-level hash.
+pub(crate) fn signed_hash<'a>(message: &'a serde_json::Value, part_name: &str) -> Option<&'a str> {
+    message["file_metadata"][part_name]["hash"]
+        .as_str()
+        .or_else(|| message["hash"].as_str())
+}
+
+// Serializes a document message with the hash of each file embedded where multipart_form looks
+// for it; the returned string is what gets signed and sent as SignedMessageParams.message.
+pub fn multipart_message<T: Serialize>(
+    message: &T,
+    files: &[MultipartFile],
+) -> Result<String, Box<dyn std::error::Error + Sync + Send>> {
+    let mut value = serde_json::to_value(message)?;
+
+    let object = match value.as_object_mut() {
+        Some(x) => x,
+        None => return Err(Box::from("a multipart message must be a JSON object")),
+    };
+
+    match files {
+        [] => return Err(Box::from("a multipart message needs at least one file")),
+        [file] => {
+            object.insert("hash".to_string(), serde_json::Value::from(file.hash()));
+        }
+        _ => {
+            let metadata = match object
+                .entry("file_metadata")
+                .or_insert_with(|| serde_json::json!({}))
+                .as_object_mut()
+            {
+                Some(x) => x,
+                None => return Err(Box::from("file_metadata must be a JSON object")),
+            };
+
+            for file in files {
+                match metadata
+                    .entry(file.name.clone())
+                    .or_insert_with(|| serde_json::json!({}))
+                    .as_object_mut()
+                {
+                    Some(x) => {
+                        x.insert("hash".to_string(), serde_json::Value::from(file.hash()));
+                    }
+                    None => return Err(Box::from(format!("file_metadata.{} must be a JSON object", file.name))),
+                }
+            }
+        }
+    }
+
+    Ok(serde_json::to_string(&value)?)
+}
+
+pub fn multipart_form(
+    params: &SignedMessageParams,
+    files: Vec<MultipartFile>,
+) -> Result<reqwest::multipart::Form, Box<dyn std::error::Error + Sync + Send>> {
+    let message = serde_json::from_str::<serde_json::Value>(&params.message)?;
+
+    if files.is_empty() {
+        error!("multipart request without files: {}", params.message);
+        return Err(Box::from("a multipart request needs at least one file"));
+    }
+
+    for file in &files {
+        match signed_hash(&message, &file.name) {
+            Some(x) if x.eq_ignore_ascii_case(&file.hash()) => {}
+            _ => {
+                error!("multipart file hash not present in signed message: {}", file);
+                return Err(Box::from(format!(
+                    "signed message does not contain the hash of {}",
+                    file.filename
+                )));
+            }
+        }
+    }
+
+    // the data part must be the exact string that was signed, so it is never re-serialized
+    let mut form = reqwest::multipart::Form::new().text("data", params.message.clone());
+
+    for file in files {
+        let part = reqwest::multipart::Part::bytes(file.data)
+            .file_name(file.filename)
+            .mime_str(&file.mime_type)?;
+        form = form.part(file.name, part);
+    }
+
+    Ok(form)
+}
+
+// Sends a document endpoint's message and files through execute, so middleware, tracing, stats
+// and the timeout apply as they do to every other call.
+pub async fn send_multipart<E: SilaEndpoint>(
+    params: &SignedMessageParams,
+    files: Vec<MultipartFile>,
+) -> Result<E::Response, Box<dyn std::error::Error + Sync + Send>> {
+    run::<E>(params, &[], Payload::Multipart(files)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(message: String) -> SignedMessageParams {
+        SignedMessageParams {
+            sila_handle: Option::None,
+            message,
+            usersignature: Option::None,
+            authsignature: String::new(),
+        }
+    }
+
+    fn file(name: &str, data: &[u8]) -> MultipartFile {
+        MultipartFile::new(name, &format!("{}.png", name), "image/png", data.to_vec())
+    }
+
+    #[test]
+    fn embeds_and_accepts_file_hashes() {
+        let message = serde_json::json!({"header": {"user_handle": "user"}, "document_type": "id_drivers_license"});
+
+        let single = [file("file", b"front")];
+        let signed = multipart_message(&message, &single).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&signed).unwrap();
+        assert_eq!(value["hash"], single[0].hash());
+        assert!(multipart_form(&params(signed), single.to_vec()).is_ok());
+
+        let several = [file("file_1", b"front"), file("file_2", b"back")];
+        let signed = multipart_message(&message, &several).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&signed).unwrap();
+        assert_eq!(value["file_metadata"]["file_2"]["hash"], several[1].hash());
+        assert!(multipart_form(&params(signed), several.to_vec()).is_ok());
+    }
+
+    #[test]
+    fn refuses_files_not_bound_to_the_signed_hash() {
+        let front = file("file", b"front");
+        let back = file("file", b"back");
+
+        // the hash appearing elsewhere in the message does not bind the file
+        let message = serde_json::json!({"hash": back.hash(), "description": front.hash()});
+        assert!(multipart_form(&params(message.to_string()), vec![front.clone()]).is_err());
+
+        let message = serde_json::json!({"description": front.hash()});
+        assert!(multipart_form(&params(message.to_string()), vec![front.clone()]).is_err());
+
+        // each part is checked against its own metadata entry
+        let several = [file("file_1", b"front"), file("file_2", b"back")];
+        let message = serde_json::json!({"file_metadata": {
+            "file_1": {"hash": several[1].hash()},
+            "file_2": {"hash": several[0].hash()},
+        }});
+        assert!(multipart_form(&params(message.to_string()), several.to_vec()).is_err());
+
+        assert!(multipart_form(&params("not json".to_string()), vec![front]).is_err());
+    }
+
+    #[test]
+    fn refuses_no_files_and_metadata_that_is_not_an_object() {
+        let message = serde_json::json!({"header": {"user_handle": "user"}});
+        assert!(multipart_message(&message, &[]).is_err());
+        assert!(multipart_form(&params(message.to_string()), Vec::new()).is_err());
+
+        let several = [file("file_1", b"front"), file("file_2", b"back")];
+
+        let message = serde_json::json!({"file_metadata": {"file_1": "front", "file_2": {}}});
+        let error = multipart_message(&message, &several).err().unwrap();
+        assert!(error.to_string().contains("file_metadata.file_1"));
+
+        let message = serde_json::json!({"file_metadata": ["file_1", "file_2"]});
+        assert!(multipart_message(&message, &several).is_err());
+
+        // other metadata supplied by the caller is kept alongside the hash
+        let message = serde_json::json!({"file_metadata": {"file_1": {"description": "front"}}});
+        let signed = multipart_message(&message, &several).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&signed).unwrap();
+        assert_eq!(value["file_metadata"]["file_1"]["description"], "front");
+        assert_eq!(value["file_metadata"]["file_1"]["hash"], several[0].hash());
+    }
+}
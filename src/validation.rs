@@ -0,0 +1,655 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AddPhoneMessage, AddPhoneMessageParams, ConfirmSmsMessage, IdentityAlias, RegisterMessage,
+    RegisterMessageParams, UpdateAddressMessage, UpdateAddressMessageParams, UpdateEmailMessage,
+    UpdateEmailMessageParams, UpdateIdentityMessage, UpdateIdentityMessageParams,
+    UpdatePhoneMessage, UpdatePhoneMessageParams,
+};
+
+// Client-side checks for the fields Sila rejects most often. Errors are collected rather than
+// returned on the first failure, keyed the way Sila keys validation_details: by message section
+// (entity, address, contact, identity) for register, and by field for the update endpoints.
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum ValidationDetail {
+    Message(String),
+    Messages(Vec<String>),
+    Fields(BTreeMap<String, ValidationDetail>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct ValidationErrors {
+    pub validation_details: BTreeMap<String, ValidationDetail>,
+}
+
+impl ValidationErrors {
+    // A key that already holds a message keeps it: a second message for a field turns it into
+    // Messages, and a field error for a section that holds messages is added as "field: message".
+    pub fn add(&mut self, section: Option<&str>, field: &str, message: &str) {
+        let details = match section {
+            Some(x) => {
+                let entry = self
+                    .validation_details
+                    .entry(x.to_string())
+                    .or_insert_with(|| ValidationDetail::Fields(BTreeMap::new()));
+
+                match entry {
+                    ValidationDetail::Fields(x) => x,
+                    _ => return push(entry, format!("{}: {}", field, message)),
+                }
+            }
+            None => &mut self.validation_details,
+        };
+
+        match details.get_mut(field) {
+            Some(x) => push(x, message.to_string()),
+            None => {
+                details.insert(field.to_string(), ValidationDetail::Message(message.to_string()));
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.validation_details.is_empty()
+    }
+
+    // (path, message) pairs, e.g. ("contact.phone", "must be an E.164 phone number").
+    pub fn errors(&self) -> Vec<(String, String)> {
+        fn walk(prefix: &str, details: &BTreeMap<String, ValidationDetail>, out: &mut Vec<(String, String)>) {
+            for (key, detail) in details {
+                let path = match prefix {
+                    "" => key.clone(),
+                    x => format!("{}.{}", x, key),
+                };
+
+                match detail {
+                    ValidationDetail::Message(x) => out.push((path, x.clone())),
+                    ValidationDetail::Messages(x) => out.extend(x.iter().map(|m| (path.clone(), m.clone()))),
+                    ValidationDetail::Fields(x) => walk(&path, x, out),
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        walk("", &self.validation_details, &mut out);
+        out
+    }
+
+    pub fn get(&self, path: &str) -> Option<String> {
+        self.errors()
+            .into_iter()
+            .find(|(p, _)| p == path)
+            .map(|(_, m)| m)
+    }
+
+    fn into_result(self) -> Result<(), ValidationErrors> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(self),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors: Vec<String> = self
+            .errors()
+            .into_iter()
+            .map(|(path, message)| format!("{}: {}", path, message))
+            .collect();
+
+        write!(f, "validation failed ({})", errors.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+// A key that already holds an error keeps it: the key becomes Messages, with any nested fields
+// kept as "path: message".
+fn push(detail: &mut ValidationDetail, message: String) {
+    let mut messages = match std::mem::replace(detail, ValidationDetail::Messages(Vec::new())) {
+        ValidationDetail::Message(x) => vec![x],
+        ValidationDetail::Messages(x) => x,
+        ValidationDetail::Fields(x) => ValidationErrors { validation_details: x }
+            .errors()
+            .into_iter()
+            .map(|(path, message)| format!("{}: {}", path, message))
+            .collect(),
+    };
+
+    messages.push(message);
+    *detail = ValidationDetail::Messages(messages);
+}
+
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+const STATES: [&str; 56] = [
+    "AL", "AK", "AZ", "AR", "CA", "CO", "CT", "DE", "DC", "FL", "GA", "HI", "ID", "IL", "IN", "IA",
+    "KS", "KY", "LA", "ME", "MD", "MA", "MI", "MN", "MS", "MO", "MT", "NE", "NV", "NH", "NJ", "NM",
+    "NY", "NC", "ND", "OH", "OK", "OR", "PA", "RI", "SC", "SD", "TN", "TX", "UT", "VT", "VA", "WA",
+    "WV", "WI", "WY", "AS", "GU", "MP", "PR", "VI",
+];
+
+const MINIMUM_AGE: i32 = 18;
+
+fn digits(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+pub fn validate_ssn(ssn: &str) -> Result<(), String> {
+    let parts: Vec<&str> = match ssn.contains('-') {
+        true => ssn.split('-').collect(),
+        false if ssn.len() == 9 && ssn.is_ascii() => vec![&ssn[0..3], &ssn[3..5], &ssn[5..9]],
+        false => vec![ssn],
+    };
+
+    match parts.as_slice() {
+        [area, group, serial]
+            if area.len() == 3 && group.len() == 2 && serial.len() == 4
+                && [area, group, serial].iter().all(|x| digits(x)) =>
+        {
+            if *area == "000" || *area == "666" || area.starts_with('9') {
+                Err("SSN area number is not valid".to_string())
+            } else if *group == "00" || *serial == "0000" {
+                Err("SSN group and serial numbers cannot be zero".to_string())
+            } else {
+                Ok(())
+            }
+        }
+        _ => Err("must be a 9 digit SSN, optionally formatted as XXX-XX-XXXX".to_string()),
+    }
+}
+
+pub fn validate_ein(ein: &str) -> Result<(), String> {
+    let ein = match ein.split_once('-') {
+        Some((prefix, rest)) if prefix.len() == 2 => format!("{}{}", prefix, rest),
+        _ => ein.to_string(),
+    };
+
+    match ein.len() == 9 && digits(&ein) {
+        true => Ok(()),
+        false => Err("must be a 9 digit EIN, optionally formatted as XX-XXXXXXX".to_string()),
+    }
+}
+
+pub fn validate_state(state: &str) -> Result<(), String> {
+    match STATES.contains(&state) {
+        true => Ok(()),
+        false => Err("must be a two letter uppercase US state or territory code".to_string()),
+    }
+}
+
+pub fn validate_postal_code(postal_code: &str) -> Result<(), String> {
+    let valid = match postal_code.split_once('-') {
+        Some((zip, plus4)) => zip.len() == 5 && digits(zip) && plus4.len() == 4 && digits(plus4),
+        None => postal_code.len() == 5 && digits(postal_code),
+    };
+
+    match valid {
+        true => Ok(()),
+        false => Err("must be a 5 digit ZIP code or ZIP+4".to_string()),
+    }
+}
+
+pub fn validate_phone(phone: &str) -> Result<(), String> {
+    let valid = match phone.strip_prefix('+') {
+        Some(x) => (8..=15).contains(&x.len()) && digits(x) && !x.starts_with('0'),
+        None => false,
+    };
+
+    match valid {
+        true => Ok(()),
+        false => Err("must be an E.164 phone number such as +15035550100".to_string()),
+    }
+}
+
+pub fn validate_email(email: &str) -> Result<(), String> {
+    let valid = match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.chars().any(char::is_whitespace)
+        }
+        None => false,
+    };
+
+    match valid {
+        true => Ok(()),
+        false => Err("must be a valid email address".to_string()),
+    }
+}
+
+pub fn validate_birthdate(birthdate: &str, today: NaiveDate) -> Result<(), String> {
+    let date = NaiveDate::parse_from_str(birthdate, "%Y-%m-%d")
+        .map_err(|_| "must be a date formatted as YYYY-MM-DD".to_string())?;
+
+    let mut age = today.year() - date.year();
+    if (today.month(), today.day()) < (date.month(), date.day()) {
+        age -= 1;
+    }
+
+    if date > today {
+        Err("cannot be in the future".to_string())
+    } else if age < MINIMUM_AGE {
+        Err(format!("must be at least {} years ago", MINIMUM_AGE))
+    } else {
+        Ok(())
+    }
+}
+
+fn check(errors: &mut ValidationErrors, section: Option<&str>, field: &str, result: Result<(), String>) {
+    if let Err(x) = result {
+        errors.add(section, field, &x);
+    }
+}
+
+fn required(value: &str) -> Result<(), String> {
+    match value.trim().is_empty() {
+        true => Err("is required".to_string()),
+        false => Ok(()),
+    }
+}
+
+fn check_identity(errors: &mut ValidationErrors, section: Option<&str>, alias: &str, value: &str) {
+    let result = match alias.to_uppercase().as_str() {
+        "SSN" => validate_ssn(value),
+        "EIN" => validate_ein(value),
+        _ => required(value),
+    };
+
+    check(errors, section, "identity_value", result);
+}
+
+struct AddressFields<'a> {
+    street_address_1: Option<&'a str>,
+    city: Option<&'a str>,
+    state: Option<&'a str>,
+    postal_code: Option<&'a str>,
+}
+
+// Register needs a complete address; updates only check the fields they change.
+fn check_address(errors: &mut ValidationErrors, section: Option<&str>, address: AddressFields, complete: bool) {
+    for (field, value) in [("street_address_1", address.street_address_1), ("city", address.city)] {
+        match value {
+            Some(x) => check(errors, section, field, required(x)),
+            None if complete => errors.add(section, field, "is required"),
+            None => {}
+        }
+    }
+
+    for (field, value, validator) in [
+        ("state", address.state, validate_state as fn(&str) -> Result<(), String>),
+        ("postal_code", address.postal_code, validate_postal_code),
+    ] {
+        match value {
+            Some(x) => check(errors, section, field, validator(x)),
+            None if complete => errors.add(section, field, "is required"),
+            None => {}
+        }
+    }
+}
+
+impl Validate for RegisterMessageParams {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        let today = Utc::now().date_naive();
+
+        check(&mut errors, Some("entity"), "first_name", required(&self.first_name));
+        check(&mut errors, Some("entity"), "last_name", required(&self.last_name));
+        check(&mut errors, Some("entity"), "birthdate", validate_birthdate(&self.birthdate, today));
+
+        check_address(
+            &mut errors,
+            Some("address"),
+            AddressFields {
+                street_address_1: Some(&self.street_address_1),
+                city: Some(&self.city),
+                state: Some(&self.state),
+                postal_code: Some(&self.postal_code),
+            },
+            true,
+        );
+
+        check(&mut errors, Some("contact"), "phone", validate_phone(&self.phone));
+        check(&mut errors, Some("contact"), "email", validate_email(&self.email));
+        check(&mut errors, Some("identity"), "identity_value", validate_ssn(&self.ssn));
+
+        errors.into_result()
+    }
+}
+
+// Sections left out of the message (as the RegistrationBuilder allows) are not checked.
+impl Validate for RegisterMessage {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        let today = Utc::now().date_naive();
+
+        check(&mut errors, Some("entity"), "first_name", required(&self.entity.first_name));
+        check(&mut errors, Some("entity"), "last_name", required(&self.entity.last_name));
+
+        if let Some(x) = &self.entity.birthdate {
+            check(&mut errors, Some("entity"), "birthdate", validate_birthdate(x, today));
+        }
+
+        if let Some(x) = &self.address {
+            check_address(
+                &mut errors,
+                Some("address"),
+                AddressFields {
+                    street_address_1: x.street_address_1.as_deref(),
+                    city: x.city.as_deref(),
+                    state: x.state.as_deref(),
+                    postal_code: x.postal_code.as_deref(),
+                },
+                true,
+            );
+        }
+
+        if let Some(x) = &self.contact {
+            if let Some(phone) = &x.phone {
+                check(&mut errors, Some("contact"), "phone", validate_phone(phone));
+            }
+            if let Some(email) = &x.email {
+                check(&mut errors, Some("contact"), "email", validate_email(email));
+            }
+        }
+
+        if let Some(x) = &self.identity {
+            let alias = match x.identity_alias {
+                IdentityAlias::Ssn => "SSN",
+                IdentityAlias::Ein => "EIN",
+            };
+            check_identity(&mut errors, Some("identity"), alias, &x.identity_value);
+        }
+
+        errors.into_result()
+    }
+}
+
+// The update endpoints check the same fields whether they start from params or a message.
+fn check_address_update(address: AddressFields) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    check_address(&mut errors, Option::None, address, false);
+    errors.into_result()
+}
+
+fn check_phone_update(phone: Option<&str>) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+
+    if let Some(x) = phone {
+        check(&mut errors, Option::None, "phone", validate_phone(x));
+    }
+
+    errors.into_result()
+}
+
+fn check_email_update(email: &str) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    check(&mut errors, Option::None, "email", validate_email(email));
+    errors.into_result()
+}
+
+fn check_identity_update(identity_alias: &str, identity_value: &str) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    check(&mut errors, Option::None, "identity_alias", required(identity_alias));
+    check_identity(&mut errors, Option::None, identity_alias, identity_value);
+    errors.into_result()
+}
+
+impl Validate for UpdateAddressMessageParams {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        check_address_update(AddressFields {
+            street_address_1: self.street_address_1.as_deref(),
+            city: self.city.as_deref(),
+            state: self.state.as_deref(),
+            postal_code: self.postal_code.as_deref(),
+        })
+    }
+}
+
+impl Validate for UpdateAddressMessage {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        check_address_update(AddressFields {
+            street_address_1: self.street_address_1.as_deref(),
+            city: self.city.as_deref(),
+            state: self.state.as_deref(),
+            postal_code: self.postal_code.as_deref(),
+        })
+    }
+}
+
+impl Validate for UpdatePhoneMessageParams {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        check_phone_update(self.phone.as_deref())
+    }
+}
+
+impl Validate for UpdatePhoneMessage {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        check_phone_update(self.phone.as_deref())
+    }
+}
+
+impl Validate for AddPhoneMessageParams {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        check_phone_update(Some(&self.phone))
+    }
+}
+
+impl Validate for AddPhoneMessage {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        check_phone_update(Some(&self.phone))
+    }
+}
+
+impl Validate for ConfirmSmsMessage {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check(&mut errors, Option::None, "uuid", required(&self.uuid));
+
+        let code = match self.code.is_empty() || !self.code.chars().all(|x| x.is_ascii_digit()) {
+            true => Err("must be the numeric code sent by SMS".to_string()),
+            false => Ok(()),
+        };
+        check(&mut errors, Option::None, "code", code);
+
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdateEmailMessageParams {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        check_email_update(&self.email)
+    }
+}
+
+impl Validate for UpdateEmailMessage {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        check_email_update(&self.email)
+    }
+}
+
+impl Validate for UpdateIdentityMessageParams {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        check_identity_update(&self.identity_alias, &self.identity_value)
+    }
+}
+
+impl Validate for UpdateIdentityMessage {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        check_identity_update(&self.identity_alias, &self.identity_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web3::types::H160;
+
+    fn register_params() -> RegisterMessageParams {
+        RegisterMessageParams {
+            sila_handle: "user".to_string(),
+            ethereum_address: H160::zero(),
+            birthdate: "1990-01-01".to_string(),
+            first_name: "Ada".to_string(),
+            last_name: "Lovelace".to_string(),
+            street_address_1: "1 Main St".to_string(),
+            city: "Portland".to_string(),
+            state: "OR".to_string(),
+            postal_code: "97201".to_string(),
+            phone: "+15035550100".to_string(),
+            email: "ada@example.com".to_string(),
+            ssn: "123-45-6222".to_string(),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_registration() {
+        assert!(register_params().validate().is_ok());
+    }
+
+    #[test]
+    fn reports_every_invalid_field_by_section() {
+        let params = RegisterMessageParams {
+            birthdate: "2015-06-01".to_string(),
+            state: "Oregon".to_string(),
+            postal_code: "9720".to_string(),
+            phone: "503-555-0100".to_string(),
+            email: "ada.example.com".to_string(),
+            ssn: "666-45-6222".to_string(),
+            ..register_params()
+        };
+
+        let errors = params.validate().unwrap_err();
+        let paths: Vec<String> = errors.errors().into_iter().map(|(p, _)| p).collect();
+
+        assert_eq!(
+            paths,
+            vec![
+                "address.postal_code",
+                "address.state",
+                "contact.email",
+                "contact.phone",
+                "entity.birthdate",
+                "identity.identity_value",
+            ]
+        );
+
+        let serialized = serde_json::to_value(&errors).unwrap();
+        assert!(serialized["validation_details"]["contact"]["phone"].is_string());
+    }
+
+    #[test]
+    fn parses_sila_validation_details() {
+        let errors: ValidationErrors = serde_json::from_str(
+            r#"{"validation_details": {"address": {"postal_code": "Invalid postal code"}, "header": ["bad"]}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(errors.get("address.postal_code").as_deref(), Some("Invalid postal code"));
+        assert_eq!(errors.get("header").as_deref(), Some("bad"));
+    }
+
+    #[test]
+    fn birthdate_requires_adulthood() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+
+        assert!(validate_birthdate("2006-06-15", today).is_ok());
+        assert!(validate_birthdate("2006-06-16", today).is_err());
+        assert!(validate_birthdate("2030-01-01", today).is_err());
+        assert!(validate_birthdate("06/15/2006", today).is_err());
+    }
+
+    #[test]
+    fn field_formats() {
+        assert!(validate_ssn("123456222").is_ok());
+        assert!(validate_ssn("123-45-6222").is_ok());
+        assert!(validate_ssn("912-45-6222").is_err());
+        assert!(validate_ssn("123-00-6222").is_err());
+        assert!(validate_ssn("12345622").is_err());
+
+        assert!(validate_ein("12-3456789").is_ok());
+        assert!(validate_ein("123456789").is_ok());
+        assert!(validate_ein("1234-56789").is_err());
+
+        assert!(validate_postal_code("97201-1234").is_ok());
+        assert!(validate_postal_code("97201-12").is_err());
+
+        assert!(validate_phone("+442071838750").is_ok());
+        assert!(validate_phone("+0123456789").is_err());
+        assert!(validate_phone("15035550100").is_err());
+
+        assert!(validate_state("DC").is_ok());
+        assert!(validate_state("or").is_err());
+
+        assert!(validate_email("a@b.co").is_ok());
+        assert!(validate_email("a@b").is_err());
+        assert!(validate_email("a b@c.co").is_err());
+    }
+
+    #[test]
+    fn update_params_only_check_supplied_fields() {
+        let params = UpdateAddressMessageParams {
+            sila_handle: "user".to_string(),
+            ethereum_address: H160::zero(),
+            uuid: "uuid".to_string(),
+            address_alias: Option::None,
+            street_address_1: Option::None,
+            street_address_2: Option::None,
+            city: Option::None,
+            state: Option::None,
+            postal_code: Option::from("ABCDE".to_string()),
+            country: Option::None,
+        };
+
+        let errors = params.validate().unwrap_err();
+        assert_eq!(errors.errors().len(), 1);
+        assert!(errors.get("postal_code").is_some());
+
+        let params = UpdateIdentityMessageParams {
+            sila_handle: "user".to_string(),
+            ethereum_address: H160::zero(),
+            uuid: "uuid".to_string(),
+            identity_alias: "EIN".to_string(),
+            identity_value: "12-3456789".to_string(),
+        };
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn keeps_every_error_added_under_one_key() {
+        let mut errors = ValidationErrors::default();
+        errors.add(Option::None, "phone", "is required");
+        errors.add(Option::None, "phone", "must be an E.164 phone number such as +15035550100");
+        errors.add(Option::None, "address", "is not valid");
+        errors.add(Some("address"), "city", "is required");
+
+        assert_eq!(
+            errors.errors(),
+            vec![
+                ("address".to_string(), "is not valid".to_string()),
+                ("address".to_string(), "city: is required".to_string()),
+                ("phone".to_string(), "is required".to_string()),
+                ("phone".to_string(), "must be an E.164 phone number such as +15035550100".to_string()),
+            ]
+        );
+
+        let mut errors = ValidationErrors::default();
+        errors.add(Some("address"), "city", "is required");
+        errors.add(Option::None, "address", "is not valid");
+        assert_eq!(
+            errors.validation_details["address"],
+            ValidationDetail::Messages(vec!["city: is required".to_string(), "is not valid".to_string()])
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use chrono::{DateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::Duration;
+
+use crate::{SilaAmount, TransactionStatus, TransactionTracker, TransactionType, Transition};
+
+pub const SIGNATURE_HEADER: &str = "x-sila-signature";
+
+#[derive(Debug)]
+pub enum WebhookError {
+    MissingSignature,
+    InvalidSignature,
+    StaleTimestamp(DateTime<Utc>),
+    Malformed(String),
+}
+
+impl std::fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookError::MissingSignature => write!(f, "missing {} header", SIGNATURE_HEADER),
+            WebhookError::InvalidSignature => write!(f, "webhook signature does not match"),
+            WebhookError::StaleTimestamp(x) => write!(f, "webhook event_time {} is outside the allowed window", x),
+            WebhookError::Malformed(x) => write!(f, "malformed webhook payload: {}", x),
+        }
+    }
+}
+
+impl std::error::Error for WebhookError {}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct TransactionEvent {
+    pub transaction: Option<String>,
+    pub transaction_type: Option<TransactionType>,
+    pub entity: Option<String>,
+    pub outcome: Option<TransactionStatus>,
+    pub processing_type: Option<String>,
+    pub sila_amount: Option<SilaAmount>,
+    pub reference_id: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct KycEvent {
+    pub entity: Option<String>,
+    pub entity_type: Option<String>,
+    pub outcome: Option<String>,
+    pub kyc_level: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct VirtualAccountEvent {
+    pub virtual_account_id: Option<String>,
+    pub entity: Option<String>,
+    pub outcome: Option<String>,
+    pub transaction: Option<String>,
+    pub sila_amount: Option<SilaAmount>,
+}
+
+#[derive(Clone)]
+pub enum WebhookEvent {
+    Transaction(TransactionEvent),
+    Kyc(KycEvent),
+    VirtualAccount(VirtualAccountEvent),
+    Unknown(serde_json::Value),
+}
+
+#[derive(Deserialize)]
+struct WebhookEnvelope {
+    event_uuid: Option<String>,
+    event_type: String,
+    event_time: serde_json::Value,
+    #[serde(default)]
+    event_details: serde_json::Value,
+}
+
+pub struct WebhookDelivery {
+    pub event_uuid: Option<String>,
+    pub event_type: String,
+    pub event_time: DateTime<Utc>,
+    pub event: WebhookEvent,
+}
+
+impl std::fmt::Display for WebhookDelivery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WebhookDelivery(event_uuid: {}, event_type: {}, event_time: {})",
+            self.event_uuid.as_ref().unwrap_or(&"none".to_string()),
+            self.event_type,
+            self.event_time.to_rfc3339()
+        )
+    }
+}
+
+fn parse_event_time(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    match value {
+        serde_json::Value::Number(x) => x.as_i64().and_then(|x| Utc.timestamp_opt(x, 0).single()),
+        serde_json::Value::String(x) => DateTime::parse_from_rfc3339(x)
+            .ok()
+            .map(|x| x.with_timezone(&Utc))
+            .or_else(|| x.parse::<i64>().ok().and_then(|x| Utc.timestamp_opt(x, 0).single())),
+        _ => Option::None,
+    }
+}
+
+fn typed_event<T: serde::de::DeserializeOwned>(
+    details: serde_json::Value,
+    wrap: fn(T) -> WebhookEvent,
+) -> WebhookEvent {
+    match serde_json::from_value::<T>(details.clone()) {
+        Ok(x) => wrap(x),
+        Err(e) => {
+            warn!("webhook event_details did not match the expected shape ({}): {}", e, details);
+            WebhookEvent::Unknown(details)
+        }
+    }
+}
+
+pub fn sign_webhook(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// Framework-agnostic: hand it the raw request body and the request headers as name/value
+// pairs, exactly as received.
+pub struct WebhookVerifier {
+    secret: String,
+    tolerance: Duration,
+}
+
+impl WebhookVerifier {
+    pub fn new(secret: &str) -> Self {
+        WebhookVerifier {
+            secret: secret.to_string(),
+            tolerance: Duration::from_secs(300),
+        }
+    }
+
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn verify_signature(&self, body: &[u8], signature: &str) -> Result<(), WebhookError> {
+        let expected = hex::decode(signature.trim()).map_err(|_| WebhookError::InvalidSignature)?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC accepts any key length");
+        mac.update(body);
+        mac.verify_slice(&expected).map_err(|_| WebhookError::InvalidSignature)
+    }
+
+    pub fn verify<I, K, V>(&self, body: &[u8], headers: I) -> Result<WebhookDelivery, WebhookError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let signature = headers
+            .into_iter()
+            .find(|(k, _)| k.as_ref().eq_ignore_ascii_case(SIGNATURE_HEADER))
+            .map(|(_, v)| v.as_ref().to_string())
+            .ok_or(WebhookError::MissingSignature)?;
+
+        if let Err(e) = self.verify_signature(body, &signature) {
+            error!("rejected webhook: {}", e);
+            return Err(e);
+        }
+
+        let envelope: WebhookEnvelope =
+            serde_json::from_slice(body).map_err(|e| WebhookError::Malformed(e.to_string()))?;
+
+        let event_time = parse_event_time(&envelope.event_time)
+            .ok_or_else(|| WebhookError::Malformed("unreadable event_time".to_string()))?;
+
+        let age = Utc::now().signed_duration_since(event_time);
+        let tolerance = chrono::Duration::from_std(self.tolerance)
+            .map_err(|e| WebhookError::Malformed(e.to_string()))?;
+
+        if age > tolerance || age < -tolerance {
+            error!("rejected stale webhook {:?} from {}", envelope.event_uuid, event_time);
+            return Err(WebhookError::StaleTimestamp(event_time));
+        }
+
+        let details = envelope.event_details;
+        let event = match envelope.event_type.as_str() {
+            "transaction" | "transaction_status" => typed_event(details, WebhookEvent::Transaction),
+            "kyc" | "kyb" => typed_event(details, WebhookEvent::Kyc),
+            x if x.starts_with("virtual_account") => typed_event(details, WebhookEvent::VirtualAccount),
+            _ => WebhookEvent::Unknown(details),
+        };
+
+        Ok(WebhookDelivery {
+            event_uuid: envelope.event_uuid,
+            event_type: envelope.event_type,
+            event_time,
+            event,
+        })
+    }
+}
+
+impl TransactionTracker {
+    pub fn observe_webhook(&mut self, delivery: &WebhookDelivery) -> Option<Transition> {
+        match &delivery.event {
+            WebhookEvent::Transaction(x)
+                if x.transaction.as_deref() == Some(self.transaction_id.as_str()) =>
+            {
+                let outcome = x.outcome.clone()?;
+                Some(self.observe(outcome, Option::from(std::time::SystemTime::from(delivery.event_time))))
+            }
+            _ => Option::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(event_time: i64) -> String {
+        serde_json::json!({
+            "event_uuid": "6e0d3c22-5a3f-4c68-9e1e-0b2f7a1c9d11",
+            "event_type": "transaction",
+            "event_time": event_time,
+            "event_details": {
+                "transaction": "f1a2b3c4",
+                "transaction_type": "issue",
+                "entity": "user.silamoney.eth",
+                "outcome": "success",
+                "sila_amount": 100
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn verifies_signed_payload() {
+        let body = payload(Utc::now().timestamp());
+        let signature = sign_webhook("secret", body.as_bytes());
+
+        let delivery = WebhookVerifier::new("secret")
+            .verify(body.as_bytes(), vec![("X-Sila-Signature", signature.as_str())])
+            .unwrap();
+
+        match delivery.event {
+            WebhookEvent::Transaction(x) => assert!(x.outcome == Some(TransactionStatus::Success)),
+            _ => panic!("expected a transaction event"),
+        }
+    }
+
+    #[test]
+    fn rejects_tampered_and_stale_payloads() {
+        let verifier = WebhookVerifier::new("secret");
+
+        let body = payload(Utc::now().timestamp());
+        let signature = sign_webhook("other", body.as_bytes());
+        assert!(matches!(
+            verifier.verify(body.as_bytes(), vec![(SIGNATURE_HEADER, signature.as_str())]),
+            Err(WebhookError::InvalidSignature)
+        ));
+
+        let stale = payload(Utc::now().timestamp() - 3600);
+        let signature = sign_webhook("secret", stale.as_bytes());
+        assert!(matches!(
+            verifier.verify(stale.as_bytes(), vec![(SIGNATURE_HEADER, signature.as_str())]),
+            Err(WebhookError::StaleTimestamp(_))
+        ));
+
+        assert!(matches!(
+            verifier.verify(body.as_bytes(), Vec::<(&str, &str)>::new()),
+            Err(WebhookError::MissingSignature)
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+use lazy_static::lazy_static;
+
+use silamoney::cassette::{CassetteServer, REDACTED};
+use silamoney::*;
+
+mod common;
+use common::{configure, sign, Key};
+
+// The cassette was recorded with this app handle and key; replay does not verify signatures, so
+// the user requests are only signed by the app.
+const APP_HANDLE: &str = "cassette_app";
+
+lazy_static! {
+    static ref SERVER: CassetteServer = {
+        // recorded by tests/record_cassettes.rs
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cassettes/session.json");
+        let server = CassetteServer::replay(path).unwrap();
+        configure(&server.url(), APP_HANDLE, &Key::seeded(1));
+
+        server
+    };
+}
+
+fn entity_message(sila_handle: &str) -> HeaderMessage {
+    let _ = &*SERVER;
+    HeaderMessage::from(RequestEntityMessageParams { sila_handle: sila_handle.to_string() })
+}
+
+#[tokio::test]
+async fn get_entity_decodes_a_redacted_individual() {
+    let message = entity_message("cassette_user");
+    let response = get_entity(&sign(&message, Option::None).await).await.unwrap();
+
+    assert!(response.success);
+    assert_eq!(response.reference.as_deref(), Some(message.header.reference.as_str()));
+    assert!(response.entity_type == Some(EntityType::Individual));
+
+    let entity = response.entity.unwrap();
+    assert_eq!(entity.first_name.as_deref(), Some(REDACTED));
+    assert_eq!(entity.birthdate.as_deref(), Some(REDACTED));
+    assert!(entity.entity_type == Some(EntityType::Individual));
+    assert_eq!(response.identities.unwrap()[0].identity.as_deref(), Some("*****6222"));
+    assert_eq!(response.phones.unwrap()[0].phone.as_deref(), Some(REDACTED));
+}
+
+#[tokio::test]
+async fn get_transactions_decodes_the_recorded_issue() {
+    let _ = &*SERVER;
+    let message = GetTransactionsMessage::from(GetTransactionsMessageParams {
+        sila_handle: Option::from("cassette_user".to_string()),
+        reference: Option::None,
+        search_filters: Option::from(TransactionSearchFilters::default()),
+    });
+
+    let response = get_transactions(&sign(&message, Option::None).await).await.unwrap();
+    let transactions = response.transactions.unwrap();
+    assert_eq!(transactions.len(), 1);
+
+    let issue = &transactions[0];
+    assert!(matches!(issue.transaction_type, Some(TransactionType::Issue)));
+    assert_eq!(issue.sila_amount, Some(SilaAmount::from_sila(1000).unwrap()));
+    assert_eq!(issue.bank_account_name.as_deref(), Some("default"));
+}
+
+#[tokio::test]
+async fn pages_of_a_listing_replay_separately() {
+    let _ = &*SERVER;
+    let message = GetEntitiesMessage::from(GetEntitiesMessageParams::default());
+
+    // both pages send the same body; only the query string tells them apart
+    for page in 1..=2 {
+        let response = get_entities(&sign(&message, Option::None).await, Option::from(page), Option::from(1))
+            .await
+            .unwrap();
+
+        let pagination = response.pagination.unwrap();
+        assert_eq!(pagination.current_page, Some(page));
+        assert_eq!(pagination.total_pages, Some(2));
+        assert_eq!(response.entities.unwrap().individuals[0].full_name.as_deref(), Some(REDACTED));
+    }
+}
+
+#[tokio::test]
+async fn unrecorded_requests_are_refused() {
+    let response = get_entity(&sign(&entity_message("not_recorded"), Option::None).await).await.unwrap();
+
+    assert!(!response.success);
+    assert!(response.entity.is_none());
+}
@@ -0,0 +1,159 @@
+use std::convert::Infallible;
+use std::env;
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use lazy_static::lazy_static;
+
+use silamoney::mock::MockGateway;
+use silamoney::*;
+
+mod common;
+use common::{handle, start_gateway, Key};
+
+const APP_HANDLE: &str = "cli_app";
+const APP_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const USER_KEY: &str = "0707070707070707070707070707070707070707070707070707070707070707";
+
+lazy_static! {
+    static ref APP: Key = Key::new(APP_KEY);
+    static ref USER: Key = Key::new(USER_KEY);
+    static ref GATEWAY: MockGateway = start_gateway(APP_HANDLE, &APP);
+}
+
+// The binary inherits SILA_GATEWAY and the app settings from this process.
+fn sila(args: &[&str]) -> Output {
+    let _ = &*GATEWAY;
+
+    Command::new(env!("CARGO_BIN_EXE_sila"))
+        .args(args)
+        .env_remove("SILA_PROFILE")
+        .env_remove("SILA_USER_KEY")
+        .env("SILA_CONFIG", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cli.toml"))
+        .output()
+        .unwrap()
+}
+
+fn json(output: &Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+async fn register_user(sila_handle: &str) {
+    let _ = &*GATEWAY;
+    assert!(common::register_user(sila_handle, &USER).await.status == Status::SUCCESS);
+}
+
+#[tokio::test]
+async fn check_handle_exit_status_follows_the_response() {
+    let sila_handle = handle("cli");
+
+    let output = sila(&["-o", "json", "check-handle", &sila_handle]);
+    assert!(output.status.success());
+    assert_eq!(json(&output)["status"], "SUCCESS");
+
+    register_user(&sila_handle).await;
+
+    let output = sila(&["-o", "json", "check-handle", &sila_handle]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(json(&output)["status"], "FAILURE");
+}
+
+#[tokio::test]
+async fn kyc_and_balance_sign_with_the_profile_and_user_key() {
+    let sila_handle = handle("cli");
+    register_user(&sila_handle).await;
+
+    // user-signed commands refuse to run without a user key
+    let output = sila(&["request-kyc", &sila_handle]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = sila(&["--user-key", USER_KEY, "request-kyc", &sila_handle]);
+    assert!(output.status.success());
+
+    // the mock reports pending once before passing
+    assert_eq!(sila(&["--user-key", USER_KEY, "check-kyc", &sila_handle]).status.code(), Some(1));
+    assert!(sila(&["--user-key", USER_KEY, "check-kyc", &sila_handle]).status.success());
+
+    let output = sila(&["-o", "json", "balance", "--handle", &sila_handle]);
+    assert!(output.status.success());
+    assert_eq!(json(&output)["sila_balance"], 0);
+
+    let output = sila(&["transactions", "--handle", &sila_handle]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("TRANSACTION_ID"));
+}
+
+// A remote signer holding APP_KEY and USER_KEY, which keeps every message it is asked to sign.
+fn remote_signer() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/sign", listener.local_addr().unwrap());
+
+    let seen = requests.clone();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async move {
+            let make_service = make_service_fn(move |_| {
+                let seen = seen.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                        let seen = seen.clone();
+                        async move {
+                            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                            let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                            seen.lock().unwrap().push(request.clone());
+
+                            let signatures = default_sign(SignDataPair::from(SignDataParams {
+                                message: request["message"].as_str().unwrap().to_string(),
+                                user_params: request["user_handle"].as_str().map(|_| USER.params()),
+                                app_params: APP.params(),
+                            }))
+                            .await;
+
+                            Ok::<_, Infallible>(Response::new(Body::from(serde_json::to_vec(&signatures).unwrap())))
+                        }
+                    }))
+                }
+            });
+
+            listener.set_nonblocking(true).unwrap();
+            Server::from_tcp(listener).unwrap().serve(make_service).await.unwrap();
+        });
+    });
+
+    (url, requests)
+}
+
+#[tokio::test]
+async fn cancel_is_user_signed_by_a_remote_signer() {
+    let sila_handle = handle("cli");
+    register_user(&sila_handle).await;
+
+    let (url, requests) = remote_signer();
+    let config = env::temp_dir().join(format!("sila-{}.toml", uuid::Uuid::new_v4()));
+    std::fs::write(
+        &config,
+        format!("default_profile = \"remote\"\n\n[profiles.remote]\nsigner = {{ type = \"remote\", url = \"{}\" }}\n", url),
+    )
+    .unwrap();
+
+    let output = sila(&["--config", config.to_str().unwrap(), "-o", "json", "cancel", &sila_handle, "unknown"]);
+    std::fs::remove_file(&config).unwrap();
+
+    // the gateway verified the usersignature and only then looked for the transaction
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(json(&output)["message"], "unknown transaction_id");
+
+    // one request, signed for the user, carrying exactly the fields cancel_transaction takes
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["user_handle"], sila_handle.as_str());
+
+    let message: serde_json::Value = serde_json::from_str(requests[0]["message"].as_str().unwrap()).unwrap();
+    assert_eq!(message["header"]["user_handle"], sila_handle.as_str());
+    assert_eq!(message["header"]["auth_handle"], APP_HANDLE);
+    assert_eq!(message["transaction_id"], "unknown");
+    assert_eq!(message.as_object().unwrap().len(), 2);
+}
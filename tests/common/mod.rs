@@ -0,0 +1,105 @@
+// Fixtures shared by the integration tests. Each test binary starts its own gateway under its own
+// app handle and key, and signs as that app.
+#![allow(dead_code)]
+
+use std::env;
+use std::str::FromStr;
+
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde::Serialize;
+use sha3::{Digest, Keccak256};
+use web3::types::{H160, H256};
+
+use silamoney::mock::MockGateway;
+use silamoney::*;
+
+pub struct Key {
+    pub address: H160,
+    pub private_key: String,
+}
+
+impl Key {
+    pub fn new(private_key: &str) -> Self {
+        let secret = SecretKey::from_slice(H256::from_str(private_key).unwrap().as_bytes()).unwrap();
+        let public = PublicKey::from_secret_key(&Secp256k1::new(), &secret).serialize_uncompressed();
+
+        Key {
+            address: H160::from_slice(&Keccak256::digest(&public[1..])[12..]),
+            private_key: private_key.to_string(),
+        }
+    }
+
+    // the key made of 32 seed bytes, e.g. 0x0101...01 for 1
+    pub fn seeded(seed: u8) -> Self {
+        Key::new(&hex::encode([seed; 32]))
+    }
+
+    pub fn params(&self) -> KeyParams {
+        KeyParams {
+            address: format!("{:#x}", self.address),
+            private_key: Option::from(self.private_key.clone()),
+        }
+    }
+}
+
+// SILA_PARAMS reads these on first use, so this has to run before anything is signed or sent.
+pub fn configure(gateway: &str, app_handle: &str, app: &Key) {
+    env::set_var("SILA_GATEWAY", gateway);
+    env::set_var("SILA_APP_HANDLE", app_handle);
+    env::set_var("SILA_APP_ADDRESS", format!("{:#x}", app.address));
+    env::set_var("SILA_APP_KEY", &app.private_key);
+}
+
+pub fn start_gateway(app_handle: &str, app: &Key) -> MockGateway {
+    let gateway = MockGateway::start(app_handle, app.address).unwrap();
+    configure(&gateway.url(), app_handle, app);
+
+    gateway
+}
+
+pub fn handle(name: &str) -> String {
+    format!("{}-{}", name, uuid::Uuid::new_v4())
+}
+
+// Signs as the app passed to configure, and as user when one is given.
+pub async fn sign<T: Serialize>(message: &T, user: Option<&Key>) -> SignedMessageParams {
+    let app = Key::new(&env::var("SILA_APP_KEY").expect("configure sets SILA_APP_KEY"));
+    let message = serde_json::to_string(message).unwrap();
+
+    let signatures = default_sign(SignDataPair::from(SignDataParams {
+        message: message.clone(),
+        user_params: user.map(Key::params),
+        app_params: app.params(),
+    }))
+    .await;
+
+    SignedMessageParams {
+        sila_handle: Option::None,
+        message,
+        usersignature: signatures.usersignature,
+        authsignature: signatures.authsignature,
+    }
+}
+
+pub fn registration(sila_handle: &str, key: &Key) -> RegisterMessageParams {
+    RegisterMessageParams {
+        sila_handle: sila_handle.to_string(),
+        ethereum_address: key.address,
+        birthdate: "1990-01-01".to_string(),
+        first_name: "Test".to_string(),
+        last_name: "User".to_string(),
+        street_address_1: "1 Main St".to_string(),
+        city: "Portland".to_string(),
+        state: "OR".to_string(),
+        postal_code: "97201".to_string(),
+        phone: "+15035550100".to_string(),
+        email: "test@example.com".to_string(),
+        ssn: "123456222".to_string(),
+    }
+}
+
+pub async fn register_user(sila_handle: &str, key: &Key) -> RegisterResponse {
+    let message = RegisterMessage::from(registration(sila_handle, key));
+
+    register(&sign(&message, Option::from(key)).await).await.unwrap()
+}
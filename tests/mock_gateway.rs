@@ -0,0 +1,988 @@
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::TryStreamExt;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use lazy_static::lazy_static;
+
+use silamoney::mock::MockGateway;
+use silamoney::*;
+
+mod common;
+use common::{handle, register_user, sign, start_gateway, Key};
+
+const APP_HANDLE: &str = "mock_app";
+const APP_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+lazy_static! {
+    static ref APP: Key = Key::new(APP_KEY);
+    static ref GATEWAY: MockGateway = start_gateway(APP_HANDLE, &APP);
+}
+
+// register, pass KYC and link a "default" account
+async fn onboard(sila_handle: &str, key: &Key) {
+    assert!(register_user(sila_handle, key).await.status == Status::SUCCESS);
+
+    let kyc = HeaderMessage::from(RequestKycMessageParams { sila_handle: sila_handle.to_string() });
+    assert!(request_kyc(&sign(&kyc, Option::from(key)).await).await.unwrap().success);
+
+    let check = HeaderMessage::from(CheckKycMessageParams { sila_handle: sila_handle.to_string() });
+    loop {
+        let response = check_kyc(&sign(&check, Option::from(key)).await).await.unwrap();
+        if response.status == Status::SUCCESS {
+            break;
+        }
+    }
+
+    let link = LinkMessage::from(LinkMessageParams {
+        sila_handle: sila_handle.to_string(),
+        sila_bank_identifier: String::new(),
+        sila_bank_token: "public-sandbox-token".to_string(),
+        selected_account_id: "account".to_string(),
+        account_name: Option::None,
+    });
+    assert!(link_account(&sign(&link, Option::from(key)).await).await.unwrap().success);
+}
+
+async fn poll_until_settled(sila_handle: &str, key: &Key, transaction_id: &str) -> TransactionStatus {
+    for _ in 0..10 {
+        let message = GetTransactionsMessage::from(GetTransactionsMessageParams {
+            sila_handle: Option::from(sila_handle.to_string()),
+            reference: Option::None,
+            search_filters: Option::from(TransactionSearchFilters {
+                transaction_id: Option::from(transaction_id.to_string()),
+                ..Default::default()
+            }),
+        });
+
+        let response = get_transactions(&sign(&message, Option::from(key)).await).await.unwrap();
+        let status = response.transactions.unwrap()[0].status.clone().unwrap();
+
+        if !matches!(status, TransactionStatus::Queued | TransactionStatus::Pending) {
+            return status;
+        }
+    }
+
+    panic!("transaction {} never settled", transaction_id);
+}
+
+#[tokio::test]
+async fn register_checks_handles_and_signatures() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("register");
+    let key = Key::seeded(1);
+
+    let check = HeaderMessage::from(CheckHandleMessageParams { sila_handle: sila_handle.clone() });
+    assert!(check_handle(&sign(&check, Option::None).await).await.unwrap().status == Status::SUCCESS);
+
+    // signed by a key other than the one being registered
+    let message = RegisterMessage::from(RegisterMessageParams {
+        sila_handle: sila_handle.clone(),
+        ethereum_address: key.address,
+        birthdate: "1990-01-01".to_string(),
+        first_name: "Mock".to_string(),
+        last_name: "User".to_string(),
+        street_address_1: "1 Main St".to_string(),
+        city: "Portland".to_string(),
+        state: "OR".to_string(),
+        postal_code: "97201".to_string(),
+        phone: "+15035550100".to_string(),
+        email: "mock@example.com".to_string(),
+        ssn: "123456222".to_string(),
+    });
+    let response = register(&sign(&message, Option::from(&Key::seeded(2))).await).await.unwrap();
+    assert!(response.status == Status::FAILURE);
+    assert!(!GATEWAY.is_registered(&sila_handle));
+
+    assert!(register_user(&sila_handle, &key).await.status == Status::SUCCESS);
+    assert!(GATEWAY.is_registered(&sila_handle));
+    assert!(check_handle(&sign(&check, Option::None).await).await.unwrap().status == Status::FAILURE);
+}
+
+#[tokio::test]
+async fn rejects_a_forged_authsignature() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("forged");
+    let key = Key::seeded(3);
+    register_user(&sila_handle, &key).await;
+
+    let message = HeaderMessage::from(RequestKycMessageParams { sila_handle: sila_handle.clone() });
+    let mut params = sign(&message, Option::from(&key)).await;
+    params.authsignature = params.usersignature.clone().unwrap();
+
+    let response = request_kyc(&params).await.unwrap();
+    assert!(response.status == Status::FAILURE);
+}
+
+#[tokio::test]
+async fn issue_and_transfer_settle_through_the_scripted_statuses() {
+    let _ = &*GATEWAY;
+
+    let sender = handle("sender");
+    let sender_key = Key::seeded(4);
+    let recipient = handle("recipient");
+    let recipient_key = Key::seeded(5);
+
+    onboard(&sender, &sender_key).await;
+    onboard(&recipient, &recipient_key).await;
+
+    let issue = IssueSilaMessage::from(IssueSilaMessageParams {
+        sila_handle: sender.clone(),
+        amount: SilaAmount::from_sila(1000).unwrap(),
+        ..Default::default()
+    });
+    let response = issue_sila(&sign(&issue, Option::from(&sender_key)).await).await.unwrap();
+    assert!(response.success);
+
+    let transaction_id = response.transaction_id.unwrap();
+    assert!(poll_until_settled(&sender, &sender_key, &transaction_id).await == TransactionStatus::Success);
+    assert!(GATEWAY.balance(&sender) == Some(SilaAmount::from_sila(1000).unwrap()));
+
+    let balance = GetSilaBalanceMessage::from(GetSilaBalanceMessageParams {
+        sila_handle: Option::None,
+        address: Option::from(sender_key.address),
+    });
+    let response = get_sila_balance(&sign(&balance, Option::None).await).await.unwrap();
+    assert!(response.balance() == Some(SilaAmount::from_sila(1000).unwrap()));
+
+    let transfer = TransferSilaMessage::from(TransferSilaMessageParams {
+        sila_handle: sender.clone(),
+        amount: SilaAmount::from_sila(400).unwrap(),
+        destination_handle: recipient.clone(),
+        ..Default::default()
+    });
+    let response = transfer_sila(&sign(&transfer, Option::from(&sender_key)).await).await.unwrap();
+    assert!(response.success);
+
+    let transaction_id = response.transaction_id.unwrap();
+    assert!(poll_until_settled(&sender, &sender_key, &transaction_id).await == TransactionStatus::Success);
+    assert!(GATEWAY.balance(&sender) == Some(SilaAmount::from_sila(600).unwrap()));
+    assert!(GATEWAY.balance(&recipient) == Some(SilaAmount::from_sila(400).unwrap()));
+
+    // more than the remaining balance
+    let transfer = TransferSilaMessage::from(TransferSilaMessageParams {
+        sila_handle: sender.clone(),
+        amount: SilaAmount::from_sila(601).unwrap(),
+        destination_handle: recipient.clone(),
+        ..Default::default()
+    });
+    let response = transfer_sila(&sign(&transfer, Option::from(&sender_key)).await).await.unwrap();
+    assert!(response.status == Status::FAILURE);
+}
+
+#[tokio::test]
+async fn issue_requires_kyc() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("unverified");
+    let key = Key::seeded(6);
+    register_user(&sila_handle, &key).await;
+
+    let issue = IssueSilaMessage::from(IssueSilaMessageParams {
+        sila_handle: sila_handle.clone(),
+        amount: SilaAmount::from_sila(100).unwrap(),
+        ..Default::default()
+    });
+    let response = issue_sila(&sign(&issue, Option::from(&key)).await).await.unwrap();
+    assert!(response.status == Status::FAILURE);
+}
+
+#[tokio::test]
+async fn registers_a_kyc_lite_user_from_the_builder() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("lite");
+    let key = Key::seeded(8);
+
+    let message = RegistrationBuilder::new(&sila_handle, key.address)
+        .first_name("Lite")
+        .last_name("User")
+        .email("lite@example.com")
+        .device(Device {
+            device_fingerprint: Option::from("fingerprint".to_string()),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+
+    let body = serde_json::to_value(&message).unwrap();
+    assert!(body.get("address").is_none() && body.get("identity").is_none());
+    assert!(body["entity"]["entity_name"] == "Lite User");
+    assert!(body["contact"].get("phone").is_none());
+
+    let response = register(&sign(&message, Option::from(&key)).await).await.unwrap();
+    assert!(response.status == Status::SUCCESS);
+    assert!(GATEWAY.is_registered(&sila_handle));
+
+    // a section that is given must be complete
+    let errors = RegistrationBuilder::new(&handle("partial"), key.address)
+        .first_name("Lite")
+        .last_name("User")
+        .address(Address {
+            street_address_1: Option::from("1 Main St".to_string()),
+            street_address_2: Option::from("Apt 2".to_string()),
+            state: Option::from("OR".to_string()),
+            ..Default::default()
+        })
+        .build()
+        .err()
+        .unwrap();
+    let paths: Vec<String> = errors.errors().into_iter().map(|(p, _)| p).collect();
+    assert_eq!(paths, vec!["address.city", "address.postal_code"]);
+}
+
+// An endpoint declared outside the crate goes through the same execute path.
+struct HandleAvailability;
+
+impl SilaEndpoint for HandleAvailability {
+    type Message = HeaderMessage;
+    type Response = CheckResponse;
+
+    const PATH: &'static str = "check_handle";
+    const SIGNATURES: RequiredSignatures = RequiredSignatures::App;
+
+    fn is_failure(response: &CheckResponse) -> bool {
+        response.status == Status::FAILURE
+    }
+}
+
+#[tokio::test]
+async fn execute_runs_declared_endpoints_and_enforces_signatures() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("declared");
+    let key = Key::seeded(9);
+
+    let check = HeaderMessage::from(CheckHandleMessageParams { sila_handle: sila_handle.clone() });
+    let response = execute::<HandleAvailability>(&sign(&check, Option::None).await).await.unwrap();
+    assert!(response.status == Status::SUCCESS);
+
+    register_user(&sila_handle, &key).await;
+
+    // check_kyc needs a usersignature, so it is refused before anything is sent
+    let kyc = HeaderMessage::from(CheckKycMessageParams { sila_handle: sila_handle.clone() });
+    let error = execute::<CheckKyc>(&sign(&kyc, Option::None).await).await.err().unwrap();
+    assert!(error.to_string().contains("usersignature"));
+}
+
+// Only acts on "audited-" handles, since the chain is shared with the other tests in this binary.
+#[derive(Default)]
+struct AuditPolicy {
+    log: std::sync::Mutex<Vec<String>>,
+}
+
+impl Middleware for AuditPolicy {
+    fn on_request(
+        &self,
+        request: &mut SilaRequest,
+    ) -> Result<Option<SilaReply>, Box<dyn std::error::Error + Sync + Send>> {
+        if !request.user_handle().unwrap_or_default().starts_with("audited-") {
+            return Ok(Option::None);
+        }
+
+        let signed = request.headers.contains_key("usersignature");
+        self.log.lock().unwrap().push(format!("-> {} {}", request.path, signed));
+
+        if let Some(x) = request.message::<TransferSilaMessage>() {
+            if x.amount > SilaAmount::from_sila(100).unwrap() {
+                return Err(Box::from("transfers over 100 SILA need approval"));
+            }
+        }
+
+        if request.path == "get_sila_balance" {
+            return Ok(Option::from(SilaReply {
+                status: 503,
+                body: r#"{"success": false, "status": "FAILURE", "message": "injected"}"#.to_string(),
+            }));
+        }
+
+        Ok(Option::None)
+    }
+
+    fn on_response(
+        &self,
+        request: &SilaRequest,
+        reply: &mut SilaReply,
+    ) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        if request.user_handle().unwrap_or_default().starts_with("audited-") {
+            self.log.lock().unwrap().push(format!("<- {} {}", request.path, reply.status));
+        }
+
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn middleware_sees_every_call_and_can_refuse_or_answer_it() {
+    let _ = &*GATEWAY;
+    let policy = std::sync::Arc::new(AuditPolicy::default());
+    add_middleware(policy.clone());
+
+    let sila_handle = handle("audited");
+    let key = Key::seeded(10);
+    assert!(register_user(&sila_handle, &key).await.status == Status::SUCCESS);
+
+    let transfer = TransferSilaMessage::from(TransferSilaMessageParams {
+        sila_handle: sila_handle.clone(),
+        amount: SilaAmount::from_sila(101).unwrap(),
+        destination_handle: handle("recipient"),
+        ..Default::default()
+    });
+    let error = transfer_sila(&sign(&transfer, Option::from(&key)).await).await.err().unwrap();
+    assert!(error.to_string().contains("need approval"));
+
+    let balance = GetSilaBalanceMessage::from(GetSilaBalanceMessageParams {
+        sila_handle: Option::from(sila_handle.clone()),
+        address: Option::None,
+    });
+    let response = get_sila_balance(&sign(&balance, Option::None).await).await.unwrap();
+    assert!(response.message.as_deref() == Some("injected"));
+
+    assert_eq!(
+        *policy.log.lock().unwrap(),
+        vec![
+            "-> register true",
+            "<- register 200",
+            "-> transfer_sila true",
+            "-> get_sila_balance false",
+            "<- get_sila_balance 503",
+        ]
+    );
+}
+
+// Keeps the bodies of "verbatim-" requests.
+#[derive(Default)]
+struct BodyWatch {
+    bodies: std::sync::Mutex<Vec<String>>,
+}
+
+impl Middleware for BodyWatch {
+    fn on_request(
+        &self,
+        request: &mut SilaRequest,
+    ) -> Result<Option<SilaReply>, Box<dyn std::error::Error + Sync + Send>> {
+        if request.user_handle().unwrap_or_default().starts_with("verbatim-") {
+            self.bodies.lock().unwrap().push(request.body.to_string());
+        }
+
+        Ok(Option::None)
+    }
+}
+
+#[tokio::test]
+async fn messages_are_sent_exactly_as_they_were_signed() {
+    let _ = &*GATEWAY;
+    let watch = std::sync::Arc::new(BodyWatch::default());
+    add_middleware(watch.clone());
+
+    let sila_handle = handle("verbatim");
+    let key = Key::seeded(18);
+    assert!(register_user(&sila_handle, &key).await.status == Status::SUCCESS);
+
+    // pretty printed, so re-serializing the message would change the bytes that were signed
+    let entity = HeaderMessage::from(RequestEntityMessageParams { sila_handle });
+    let message = serde_json::to_string_pretty(&entity).unwrap();
+    let signatures = default_sign(SignDataPair::from(SignDataParams {
+        message: message.clone(),
+        user_params: Option::from(key.params()),
+        app_params: APP.params(),
+    }))
+    .await;
+
+    let params = SignedMessageParams {
+        sila_handle: Option::None,
+        message,
+        usersignature: signatures.usersignature,
+        authsignature: signatures.authsignature,
+    };
+    assert!(get_entity(&params).await.unwrap().success);
+
+    let bodies = watch.bodies.lock().unwrap();
+    assert_eq!(bodies.last(), Some(&params.message));
+}
+
+#[derive(Default)]
+struct DocumentWatch {
+    seen: std::sync::Mutex<Vec<String>>,
+}
+
+impl Middleware for DocumentWatch {
+    fn on_request(
+        &self,
+        request: &mut SilaRequest,
+    ) -> Result<Option<SilaReply>, Box<dyn std::error::Error + Sync + Send>> {
+        if request.path == UploadDocument::PATH {
+            let json = request.headers.contains_key(reqwest::header::CONTENT_TYPE);
+            self.seen.lock().unwrap().push(format!("-> {} {}", request.body, json));
+        }
+
+        Ok(Option::None)
+    }
+
+    fn on_response(
+        &self,
+        request: &SilaRequest,
+        reply: &mut SilaReply,
+    ) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        if request.path == UploadDocument::PATH {
+            self.seen.lock().unwrap().push(format!("<- {}", reply.status));
+        }
+
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn documents_are_uploaded_through_execute() {
+    let _ = &*GATEWAY;
+    let watch = std::sync::Arc::new(DocumentWatch::default());
+    add_middleware(watch.clone());
+
+    let sila_handle = handle("uploader");
+    let key = Key::seeded(12);
+    assert!(register_user(&sila_handle, &key).await.status == Status::SUCCESS);
+
+    let file = MultipartFile::new("file", "license.png", "image/png", b"front".to_vec());
+    let message = DocumentMessage::from(DocumentMessageParams {
+        sila_handle: sila_handle.clone(),
+        filename: file.filename.clone(),
+        mime_type: file.mime_type.clone(),
+        document_type: "id_drivers_license".to_string(),
+        identity_type: Option::from("license".to_string()),
+        ..Default::default()
+    });
+
+    // a file whose hash was not signed is refused before anything is sent
+    let unbound = sign(&message, Option::from(&key)).await;
+    let error = upload_document(&unbound, file.clone()).await.err().unwrap();
+    assert!(error.to_string().contains("does not contain the hash"));
+
+    // the gateway checks the signatures over the data part and the file against its hash
+    let signed: serde_json::Value =
+        serde_json::from_str(&multipart_message(&message, std::slice::from_ref(&file)).unwrap()).unwrap();
+    assert_eq!(signed["hash"], sha256_hex(b"front"));
+    let params = sign(&signed, Option::from(&key)).await;
+
+    let response = upload_document(&params, file.clone()).await.unwrap();
+    assert!(response.success, "{:?}", response.message);
+    assert_eq!(GATEWAY.documents(&sila_handle), vec![response.document_id.unwrap()]);
+
+    // signed by a key the handle was not registered with
+    let forged = sign(&signed, Option::from(&Key::seeded(19))).await;
+    let response = upload_document(&forged, file).await.unwrap();
+    assert!(!response.success);
+    assert_eq!(GATEWAY.documents(&sila_handle).len(), 1);
+
+    assert_eq!(
+        *watch.seen.lock().unwrap(),
+        vec![
+            format!("-> {} false", params.message),
+            "<- 200".to_string(),
+            format!("-> {} false", forged.message),
+            "<- 401".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn tracker_refreshes_until_the_transaction_settles() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("tracked");
+    let key = Key::seeded(13);
+    onboard(&sila_handle, &key).await;
+
+    let issue = IssueSilaMessage::from(IssueSilaMessageParams {
+        sila_handle: sila_handle.clone(),
+        amount: SilaAmount::from_sila(10).unwrap(),
+        ..Default::default()
+    });
+    let response = issue_sila(&sign(&issue, Option::from(&key)).await).await.unwrap();
+    let transaction_id = response.transaction_id.unwrap();
+
+    let user_params = key.params();
+    let sign_as_user = |message: String| {
+        let user_params = user_params.clone();
+        async move {
+            default_sign(SignDataPair::from(SignDataParams {
+                message,
+                user_params: Option::from(user_params),
+                app_params: APP.params(),
+            }))
+            .await
+        }
+    };
+
+    let mut tracker = TransactionTracker::new(&transaction_id);
+    let status = tracker.refresh(Option::from(sila_handle.clone()), &sign_as_user).await.unwrap();
+    assert!(status.is_some());
+    assert!(tracker.history().len() == 1);
+
+    let status = tracker
+        .await_terminal(
+            Option::from(sila_handle.clone()),
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+            &sign_as_user,
+        )
+        .await
+        .unwrap();
+    assert!(status == TransactionStatus::Success);
+    assert!(tracker.anomalies().is_empty());
+
+    // a handle the gateway does not know is reported rather than treated as no news
+    let mut stranger = TransactionTracker::new(&transaction_id);
+    assert!(stranger.refresh(Option::from(handle("stranger")), &sign_as_user).await.is_err());
+
+    let error = stranger
+        .await_terminal(Option::None, Duration::ZERO, Duration::ZERO, &sign_as_user)
+        .await
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("timed out"));
+}
+
+#[tokio::test]
+async fn phones_are_added_updated_and_confirmed_on_their_own_paths() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("phones");
+    let key = Key::seeded(14);
+    assert!(register_user(&sila_handle, &key).await.status == Status::SUCCESS);
+
+    let add = AddPhoneMessage::from(AddPhoneMessageParams {
+        sila_handle: sila_handle.clone(),
+        phone: "+15035550101".to_string(),
+        sms_opt_in: Option::from(true),
+    });
+    let added = add_phone(&sign(&add, Option::from(&key)).await).await.unwrap();
+    let phone_uuid = added.phone.unwrap().uuid.unwrap();
+
+    // update/phone changes the phone add/phone created rather than adding another
+    let update = UpdatePhoneMessage::from(UpdatePhoneMessageParams {
+        sila_handle: sila_handle.clone(),
+        ethereum_address: key.address,
+        uuid: phone_uuid.clone(),
+        phone: Option::from("+15035550102".to_string()),
+        sms_opt_in: Option::None,
+    });
+    let updated = update_phone(&sign(&update, Option::from(&key)).await).await.unwrap();
+    assert_eq!(updated.phone.unwrap().phone.as_deref(), Option::from("+15035550102"));
+
+    let request = SmsConfirmationMessage::from(SmsConfirmationMessageParams {
+        sila_handle: sila_handle.clone(),
+        phone_uuid: phone_uuid.clone(),
+    });
+    let requested = request_sms_confirmation(&sign(&request, Option::from(&key)).await).await.unwrap();
+    assert!(requested.phone.unwrap().is_awaiting_confirmation());
+
+    let confirm = |code: String| ConfirmSmsMessage::from(ConfirmSmsMessageParams {
+        sila_handle: sila_handle.clone(),
+        phone_uuid: phone_uuid.clone(),
+        code,
+    });
+    let code = GATEWAY.sms_code(&sila_handle, &phone_uuid).unwrap();
+    let wrong = if code == "000000" { "111111" } else { "000000" };
+    let wrong = confirm_sms(&sign(&confirm(wrong.to_string()), Option::from(&key)).await).await.unwrap();
+    assert!(!wrong.success);
+
+    let confirmed = confirm_sms(&sign(&confirm(code), Option::from(&key)).await).await.unwrap();
+    assert!(confirmed.phone.unwrap().is_confirmed());
+
+    let entity = HeaderMessage::from(RequestEntityMessageParams { sila_handle: sila_handle.clone() });
+    let entity = get_entity(&sign(&entity, Option::from(&key)).await).await.unwrap();
+    assert_eq!(entity.phones.as_ref().unwrap().len(), 2);
+    assert_eq!(entity.confirmed_phones()[0].uuid.as_deref(), Option::from(phone_uuid.as_str()));
+}
+
+#[tokio::test]
+async fn get_entities_pages_through_the_query_string() {
+    let _ = &*GATEWAY;
+    for seed in [15, 16] {
+        let key = Key::seeded(seed);
+        assert!(register_user(&handle("listed"), &key).await.status == Status::SUCCESS);
+    }
+
+    let message = GetEntitiesMessage::from(GetEntitiesMessageParams::default());
+    let response = get_entities(&sign(&message, Option::None).await, Option::from(2), Option::from(1))
+        .await
+        .unwrap();
+
+    let pagination = response.pagination.unwrap();
+    assert_eq!(pagination.current_page, Option::from(2));
+    assert_eq!(pagination.returned_count, Option::from(1));
+    assert_eq!(pagination.total_pages, pagination.total_count);
+    assert_eq!(response.entities.unwrap().individuals.len(), 1);
+}
+
+#[tokio::test]
+async fn cards_are_linked_listed_used_and_deleted() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("cardholder");
+    let key = Key::seeded(20);
+    onboard(&sila_handle, &key).await;
+
+    let link = LinkCardMessage::from(LinkCardMessageParams {
+        sila_handle: sila_handle.clone(),
+        card_name: Option::from("visa".to_string()),
+        token: "tok_visa_4242".to_string(),
+        account_postal_code: Option::from("97201".to_string()),
+        provider: Option::None,
+    });
+    let response = link_card(&sign(&link, Option::from(&key)).await).await.unwrap();
+    assert!(response.success);
+    assert_eq!(response.card_name.as_deref(), Some("visa"));
+
+    // the same name cannot be linked twice
+    assert!(!link_card(&sign(&link, Option::from(&key)).await).await.unwrap().success);
+
+    let list = GetCardsMessage::from(GetCardsMessageParams { sila_handle: sila_handle.clone() });
+    let cards = get_cards(&sign(&list, Option::from(&key)).await).await.unwrap().cards.unwrap();
+    assert_eq!(cards.len(), 1);
+    assert_eq!(cards[0].card_name.as_deref(), Some("visa"));
+    assert_eq!(cards[0].last_4.as_deref(), Some("4242"));
+
+    let issue = |card_name: Option<&str>| {
+        IssueSilaMessage::from(IssueSilaMessageParams {
+            sila_handle: sila_handle.clone(),
+            amount: SilaAmount::from_sila(50).unwrap(),
+            card_name: card_name.map(|x| x.to_string()),
+            processing_type: Option::from(IssueProcessingType::Card),
+            ..Default::default()
+        })
+    };
+
+    assert!(issue_sila(&sign(&issue(Some("visa")), Option::from(&key)).await).await.unwrap().success);
+
+    let error = issue_sila(&sign(&issue(None), Option::from(&key)).await).await.err().unwrap();
+    assert!(error.to_string().contains("requires card_name"));
+
+    let delete = DeleteCardMessage::from(DeleteCardMessageParams {
+        sila_handle: sila_handle.clone(),
+        card_name: "visa".to_string(),
+        provider: Option::None,
+    });
+    assert!(delete_card(&sign(&delete, Option::from(&key)).await).await.unwrap().success);
+    assert!(!delete_card(&sign(&delete, Option::from(&key)).await).await.unwrap().success);
+
+    let cards = get_cards(&sign(&list, Option::from(&key)).await).await.unwrap().cards.unwrap();
+    assert!(cards.is_empty());
+
+    // a deleted card no longer funds an issue
+    let response = issue_sila(&sign(&issue(Some("visa")), Option::from(&key)).await).await.unwrap();
+    assert!(!response.success);
+    assert!(response.message.contains("no linked card named visa"));
+}
+
+#[tokio::test]
+async fn undelivered_webhooks_are_collected_across_pages_and_retried_once() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("hooked");
+    let key = Key::seeded(21);
+    onboard(&sila_handle, &key).await;
+
+    let issue = IssueSilaMessage::from(IssueSilaMessageParams {
+        sila_handle: sila_handle.clone(),
+        amount: SilaAmount::from_sila(10).unwrap(),
+        ..Default::default()
+    });
+    let transaction_id = issue_sila(&sign(&issue, Option::from(&key)).await).await.unwrap().transaction_id.unwrap();
+    poll_until_settled(&sila_handle, &key, &transaction_id).await;
+
+    let signed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let app_sign = |message: String| {
+        signed.lock().unwrap().push(message.clone());
+        default_sign(SignDataPair::from(SignDataParams {
+            message,
+            user_params: Option::None,
+            app_params: APP.params(),
+        }))
+    };
+
+    let filters = |delivered: Option<bool>| WebhookSearchFilters {
+        user_handle: Option::from(sila_handle.clone()),
+        delivered,
+        per_page: Option::from(2),
+        ..Default::default()
+    };
+
+    let events: Vec<Webhook> = get_webhooks_stream(
+        GetWebhooksMessageParams {
+            sila_handle: Option::None,
+            search_filters: Option::from(filters(Option::None)),
+        },
+        &app_sign,
+    )
+    .try_collect()
+    .await
+    .unwrap();
+    assert!(events.len() >= 4, "only {} events", events.len());
+
+    // deliver one event first, so the recovery job has to filter it out
+    let delivered = events[0].uuid.clone().unwrap();
+    let retry = RetryWebhookMessage::from(RetryWebhookMessageParams { event_uuid: delivered.clone() });
+    assert!(retry_webhook(&sign(&retry, Option::None).await).await.unwrap().success);
+
+    let mut undelivered: Vec<String> = events[1..].iter().map(|x| x.uuid.clone().unwrap()).collect();
+    undelivered.sort();
+
+    // delivered=true is overridden, and every page is read before anything is retried
+    signed.lock().unwrap().clear();
+    let results = retry_undelivered_webhooks(filters(Option::from(true)), &app_sign).await.unwrap();
+
+    let mut retried: Vec<String> = results.iter().map(|(x, _)| x.clone()).collect();
+    retried.sort();
+    assert_eq!(retried, undelivered);
+    assert!(results.iter().all(|(_, x)| x.as_ref().unwrap().success));
+
+    let messages: Vec<serde_json::Value> = signed
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|x| serde_json::from_str(x).unwrap())
+        .collect();
+    let pages: Vec<&serde_json::Value> = messages.iter().take_while(|x| x.get("event_uuid").is_none()).collect();
+    assert_eq!(pages.len(), undelivered.len().div_ceil(2));
+    for (i, x) in pages.iter().enumerate() {
+        assert_eq!(x["search_filters"]["delivered"], false);
+        assert_eq!(x["search_filters"]["page"], i + 1);
+    }
+    assert_eq!(messages.len(), pages.len() + undelivered.len());
+
+    // nothing is left to retry
+    let results = retry_undelivered_webhooks(filters(Option::None), &app_sign).await.unwrap();
+    assert!(results.is_empty());
+}
+
+#[tokio::test]
+async fn virtual_accounts_are_opened_read_updated_and_closed() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("virtual");
+    let key = Key::seeded(22);
+    onboard(&sila_handle, &key).await;
+
+    let open = OpenVirtualAccountMessage::from(OpenVirtualAccountMessageParams {
+        sila_handle: sila_handle.clone(),
+        virtual_account_name: "savings".to_string(),
+        ach_debit_enabled: Option::from(true),
+        ..Default::default()
+    });
+    let response = open_virtual_account(&sign(&open, Option::from(&key)).await).await.unwrap();
+    assert!(response.success);
+
+    let opened = response.virtual_account.unwrap();
+    let virtual_account_id = opened.virtual_account_id.clone().unwrap();
+    let account_number = opened.account_number.clone().unwrap();
+    assert_eq!(opened.virtual_account_name.as_deref(), Some("savings"));
+    assert_eq!(opened.active, Some(true));
+    assert_eq!(opened.ach_debit_enabled, Some(true));
+    assert_eq!(opened.ach_credit_enabled, Some(false));
+
+    let get = |virtual_account_id: &str| {
+        GetVirtualAccountMessage::from(GetVirtualAccountMessageParams {
+            sila_handle: sila_handle.clone(),
+            virtual_account_id: virtual_account_id.to_string(),
+        })
+    };
+    let response = get_virtual_account(&sign(&get(&virtual_account_id), Option::from(&key)).await).await.unwrap();
+    assert!(response.success);
+    assert_eq!(response.virtual_account.unwrap().account_number.as_deref(), Some(account_number.as_str()));
+
+    let response = get_virtual_account(&sign(&get("missing"), Option::from(&key)).await).await.unwrap();
+    assert!(!response.success);
+
+    let list = GetVirtualAccountsMessage::from(GetVirtualAccountsMessageParams { sila_handle: sila_handle.clone() });
+    let accounts = get_virtual_accounts(&sign(&list, Option::from(&key)).await).await.unwrap().virtual_accounts.unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].virtual_account_id.as_deref(), Some(virtual_account_id.as_str()));
+
+    // only the fields sent are changed
+    let update = UpdateVirtualAccountMessage::from(UpdateVirtualAccountMessageParams {
+        sila_handle: sila_handle.clone(),
+        virtual_account_id: virtual_account_id.clone(),
+        virtual_account_name: Option::from("rainy day".to_string()),
+        statements_enabled: Option::from(true),
+        ..Default::default()
+    });
+    let response = update_virtual_account(&sign(&update, Option::from(&key)).await).await.unwrap();
+    assert!(response.success);
+
+    let updated = response.virtual_account.unwrap();
+    assert_eq!(updated.virtual_account_name.as_deref(), Some("rainy day"));
+    assert_eq!(updated.statements_enabled, Some(true));
+    assert_eq!(updated.ach_debit_enabled, Some(true));
+
+    let close = |account_number: &str| {
+        CloseVirtualAccountMessage::from(CloseVirtualAccountMessageParams {
+            sila_handle: sila_handle.clone(),
+            virtual_account_id: virtual_account_id.clone(),
+            account_number: account_number.to_string(),
+        })
+    };
+
+    // the account number has to match the account being closed
+    let response = close_virtual_account(&sign(&close("0000000000"), Option::from(&key)).await).await.unwrap();
+    assert!(!response.success);
+
+    let response = close_virtual_account(&sign(&close(&account_number), Option::from(&key)).await).await.unwrap();
+    assert!(response.success);
+
+    let closed = response.virtual_account.unwrap();
+    assert_eq!(closed.closed, Some(true));
+    assert_eq!(closed.active, Some(false));
+    assert!(closed.closed_epoch.is_some());
+
+    // a closed account can be neither closed again nor updated
+    assert!(!close_virtual_account(&sign(&close(&account_number), Option::from(&key)).await).await.unwrap().success);
+    assert!(!update_virtual_account(&sign(&update, Option::from(&key)).await).await.unwrap().success);
+}
+
+#[tokio::test]
+async fn partner_kyc_is_checked_by_app_and_user_handle() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("partner");
+    let key = Key::seeded(23);
+
+    let check = |query_app_handle: &str, query_user_handle: &str| {
+        CheckPartnerKycMessage::from(CheckPartnerKycMessageParams {
+            query_app_handle: query_app_handle.to_string(),
+            query_user_handle: query_user_handle.to_string(),
+        })
+    };
+
+    // a registered user has not been verified yet
+    register_user(&sila_handle, &key).await;
+    let response = check_partner_kyc(&sign(&check(APP_HANDLE, &sila_handle), Option::None).await).await.unwrap();
+    assert!(!response.success);
+    assert_eq!(response.verification_status.as_deref(), Some("unverified"));
+
+    let kyc = HeaderMessage::from(RequestKycMessageParams { sila_handle: sila_handle.clone() });
+    assert!(request_kyc(&sign(&kyc, Option::from(&key)).await).await.unwrap().success);
+
+    let response = check_partner_kyc(&sign(&check(APP_HANDLE, &sila_handle), Option::None).await).await.unwrap();
+    assert!(!response.success);
+    assert_eq!(response.verification_status.as_deref(), Some("pending"));
+
+    // the user's own check_kyc moves verification along; the partner check only reads it
+    let own = HeaderMessage::from(CheckKycMessageParams { sila_handle: sila_handle.clone() });
+    while check_kyc(&sign(&own, Option::from(&key)).await).await.unwrap().status != Status::SUCCESS {}
+
+    let response = check_partner_kyc(&sign(&check(APP_HANDLE, &sila_handle), Option::None).await).await.unwrap();
+    assert!(response.status == Status::SUCCESS);
+    assert!(response.entity_type == Some(EntityType::Individual));
+    assert_eq!(response.verification_status.as_deref(), Some("passed"));
+    assert_eq!(response.kyc_level.as_deref(), Some("DEFAULT"));
+
+    let response = check_partner_kyc(&sign(&check("other_app", &sila_handle), Option::None).await).await.unwrap();
+    assert!(!response.success);
+    assert!(response.message.unwrap().contains("unknown app handle other_app"));
+
+    let response = check_partner_kyc(&sign(&check(APP_HANDLE, "nobody"), Option::None).await).await.unwrap();
+    assert!(!response.success);
+    assert!(response.message.unwrap().contains("unknown user handle nobody"));
+}
+
+// A JSON-RPC node whose every eth_call returns word, keeping the calls it is sent.
+fn rpc_node(word: [u8; 32]) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let seen = calls.clone();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async move {
+            let make_service = make_service_fn(move |_| {
+                let seen = seen.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                        let seen = seen.clone();
+                        async move {
+                            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                            let call: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                            seen.lock().unwrap().push(call.clone());
+
+                            let reply = serde_json::json!({
+                                "jsonrpc": "2.0",
+                                "id": call["id"],
+                                "result": format!("0x{}", hex::encode(word)),
+                            });
+                            Ok::<_, Infallible>(Response::new(Body::from(serde_json::to_vec(&reply).unwrap())))
+                        }
+                    }))
+                }
+            });
+
+            listener.set_nonblocking(true).unwrap();
+            Server::from_tcp(listener).unwrap().serve(make_service).await.unwrap();
+        });
+    });
+
+    (url, calls)
+}
+
+#[tokio::test]
+async fn sila_balance_is_cross_checked_against_the_chain() {
+    let _ = &*GATEWAY;
+    let sila_handle = handle("onchain");
+    let key = Key::seeded(24);
+    onboard(&sila_handle, &key).await;
+
+    let issue = IssueSilaMessage::from(IssueSilaMessageParams {
+        sila_handle: sila_handle.clone(),
+        amount: SilaAmount::from_sila(250).unwrap(),
+        ..Default::default()
+    });
+    let transaction_id = issue_sila(&sign(&issue, Option::from(&key)).await).await.unwrap().transaction_id.unwrap();
+    assert!(poll_until_settled(&sila_handle, &key, &transaction_id).await == TransactionStatus::Success);
+
+    // neither an address nor a handle is refused before anything is sent
+    let nobody = GetSilaBalanceMessage::from(GetSilaBalanceMessageParams::default());
+    let error = get_sila_balance(&sign(&nobody, Option::None).await).await.err().unwrap();
+    assert!(error.to_string().contains("requires an address or a sila_handle"));
+
+    let token_address = Key::seeded(25).address;
+    let chain = |rpc_url: &str| OnChainBalanceParams {
+        rpc_url: rpc_url.to_string(),
+        token_address,
+        decimals: 18,
+        timeout: Duration::from_secs(5),
+    };
+    let balance = GetSilaBalanceMessage::from(GetSilaBalanceMessageParams {
+        sila_handle: Option::from(sila_handle.clone()),
+        address: Option::None,
+    });
+
+    // 250 SILA at 18 decimals, plus a fraction that is truncated
+    let mut word = [0u8; 32];
+    (web3::types::U256::from(250) * web3::types::U256::exp10(18) + 1).to_big_endian(&mut word);
+    let (rpc_url, calls) = rpc_node(word);
+
+    let check = cross_check_sila_balance(&sign(&balance, Option::None).await, &chain(&rpc_url), key.address)
+        .await
+        .unwrap();
+    assert!(check.matches());
+    assert!(check.api_balance == Some(SilaAmount::from_sila(250).unwrap()));
+    assert!(check.chain_balance == SilaAmount::from_sila(250).unwrap());
+
+    // one balanceOf(address) call to the token contract
+    let calls = calls.lock().unwrap().clone();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0]["method"], "eth_call");
+    assert_eq!(calls[0]["params"][0]["to"], format!("{:#x}", token_address));
+    assert_eq!(
+        calls[0]["params"][0]["data"],
+        format!("0x70a08231{:0>64}", hex::encode(key.address.as_bytes()))
+    );
+
+    let mut word = [0u8; 32];
+    (web3::types::U256::from(249) * web3::types::U256::exp10(18)).to_big_endian(&mut word);
+    let (rpc_url, _) = rpc_node(word);
+
+    let check = cross_check_sila_balance(&sign(&balance, Option::None).await, &chain(&rpc_url), key.address)
+        .await
+        .unwrap();
+    assert!(!check.matches());
+    assert!(check.chain_balance == SilaAmount::from_sila(249).unwrap());
+    assert_eq!(
+        check.to_string(),
+        format!("BalanceCrossCheck(address: {:#x}, api_balance: 250, chain_balance: 249)", key.address)
+    );
+}
@@ -0,0 +1,38 @@
+use silamoney::stats::install_prometheus;
+use silamoney::*;
+
+mod common;
+use common::{sign, start_gateway, Key};
+
+const APP_HANDLE: &str = "prometheus_app";
+
+#[tokio::test]
+async fn renders_per_endpoint_metrics() {
+    let _gateway = start_gateway(APP_HANDLE, &Key::seeded(3));
+
+    let handle = install_prometheus().unwrap();
+
+    let check = HeaderMessage::from(CheckHandleMessageParams { sila_handle: "metered".to_string() });
+    for _ in 0..2 {
+        assert!(check_handle(&sign(&check, Option::None).await).await.unwrap().status == Status::SUCCESS);
+    }
+
+    // an unregistered handle is answered with a 400 and a FAILURE body
+    let entity = HeaderMessage::from(RequestEntityMessageParams {
+        sila_handle: "metered".to_string(),
+    });
+    assert!(!get_entity(&sign(&entity, Option::None).await).await.unwrap().success);
+
+    stats::record_retry("get_entity");
+
+    let rendered = handle.render();
+    let has = |line: &str| rendered.lines().any(|x| x == line);
+
+    assert!(has(r#"sila_requests_total{endpoint="check_handle"} 2"#), "{}", rendered);
+    assert!(has(r#"sila_requests_total{endpoint="get_entity"} 1"#), "{}", rendered);
+    assert!(has(r#"sila_http_errors_total{endpoint="get_entity",status="400"} 1"#), "{}", rendered);
+    assert!(has(r#"sila_failures_total{endpoint="get_entity"} 1"#), "{}", rendered);
+    assert!(has(r#"sila_retries_total{endpoint="get_entity"} 1"#), "{}", rendered);
+    assert!(has(r#"sila_request_duration_seconds_count{endpoint="check_handle"} 2"#), "{}", rendered);
+    assert!(!rendered.contains(r#"sila_failures_total{endpoint="check_handle"}"#), "{}", rendered);
+}
@@ -0,0 +1,120 @@
+use std::env;
+
+use lazy_static::lazy_static;
+
+use silamoney::cassette::CassetteServer;
+use silamoney::mock::MockGateway;
+use silamoney::*;
+
+mod common;
+use common::{configure, register_user, sign, Key};
+
+// Rewrites tests/cassettes/session.json by driving a session through the cassette recorder:
+//
+//     cargo test --features mock --test record_cassettes -- --ignored
+//
+// The session is recorded from the mock gateway unless SILA_RECORD_GATEWAY names another one,
+// such as the sandbox; cassette_replay.rs expects the app handle and key below.
+
+const APP_HANDLE: &str = "cassette_app";
+
+// holds the recorder and any mock gateway behind it for the life of the test binary
+struct Recording {
+    _recorder: CassetteServer,
+    _gateway: Option<MockGateway>,
+}
+
+lazy_static! {
+    static ref APP: Key = Key::seeded(1);
+    static ref RECORDING: Recording = {
+        let (upstream, gateway) = match env::var("SILA_RECORD_GATEWAY") {
+            Ok(x) => (x, Option::None),
+            Err(_) => {
+                let gateway = MockGateway::start(APP_HANDLE, APP.address).unwrap();
+                (gateway.url(), Option::from(gateway))
+            }
+        };
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cassettes/session.json");
+        let recorder = CassetteServer::record(&upstream, path).unwrap();
+        configure(&recorder.url(), APP_HANDLE, &APP);
+
+        Recording {
+            _recorder: recorder,
+            _gateway: gateway,
+        }
+    };
+}
+
+#[tokio::test]
+#[ignore]
+async fn record_cassettes() {
+    let _ = &*RECORDING;
+    let sila_handle = "cassette_user";
+    let user = Key::seeded(2);
+    let user_key = Option::from(&user);
+
+    for (sila_handle, key) in [(sila_handle, &user), ("cassette_other", &Key::seeded(3))] {
+        let response = register_user(sila_handle, key).await;
+        assert!(response.status == Status::SUCCESS, "{}", response.message);
+    }
+
+    let kyc = HeaderMessage::from(RequestKycMessageParams { sila_handle: sila_handle.to_string() });
+    assert!(request_kyc(&sign(&kyc, user_key).await).await.unwrap().success);
+
+    let check = HeaderMessage::from(CheckKycMessageParams { sila_handle: sila_handle.to_string() });
+    while check_kyc(&sign(&check, user_key).await).await.unwrap().status != Status::SUCCESS {}
+
+    let link = LinkMessage::from(LinkMessageParams {
+        sila_handle: sila_handle.to_string(),
+        sila_bank_identifier: String::new(),
+        sila_bank_token: "public-sandbox-token".to_string(),
+        selected_account_id: "account".to_string(),
+        account_name: Option::None,
+    });
+    assert!(link_account(&sign(&link, user_key).await).await.unwrap().success);
+
+    let issue = IssueSilaMessage::from(IssueSilaMessageParams {
+        sila_handle: sila_handle.to_string(),
+        amount: SilaAmount::from_sila(1000).unwrap(),
+        ..Default::default()
+    });
+    assert!(issue_sila(&sign(&issue, user_key).await).await.unwrap().success);
+
+    let entity = HeaderMessage::from(RequestEntityMessageParams { sila_handle: sila_handle.to_string() });
+    let entity = get_entity(&sign(&entity, user_key).await).await.unwrap();
+    assert!(entity.success);
+
+    // update/phone and add/phone only reach the gateway when the recorder forwards the full path
+    let phone_uuid = entity.phones.unwrap()[0].uuid.clone().unwrap();
+    let update = UpdatePhoneMessage::from(UpdatePhoneMessageParams {
+        sila_handle: sila_handle.to_string(),
+        ethereum_address: user.address,
+        uuid: phone_uuid,
+        phone: Option::from("+15035550101".to_string()),
+        sms_opt_in: Option::None,
+    });
+    assert!(update_phone(&sign(&update, user_key).await).await.unwrap().success);
+
+    let add = AddPhoneMessage::from(AddPhoneMessageParams {
+        sila_handle: sila_handle.to_string(),
+        phone: "+15035550102".to_string(),
+        sms_opt_in: Option::None,
+    });
+    assert!(add_phone(&sign(&add, user_key).await).await.unwrap().success);
+
+    let transactions = GetTransactionsMessage::from(GetTransactionsMessageParams {
+        sila_handle: Option::from(sila_handle.to_string()),
+        reference: Option::None,
+        search_filters: Option::from(TransactionSearchFilters::default()),
+    });
+    assert!(get_transactions(&sign(&transactions, user_key).await).await.unwrap().success);
+
+    let entities = GetEntitiesMessage::from(GetEntitiesMessageParams::default());
+    for page in 1..=2 {
+        let response = get_entities(&sign(&entities, Option::None).await, Option::from(page), Option::from(1))
+            .await
+            .unwrap();
+        assert!(response.success);
+    }
+}
@@ -0,0 +1,73 @@
+use opentelemetry::trace::{SpanKind, Status as SpanStatus, TracerProvider};
+use opentelemetry::Value;
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+use tracing_subscriber::layer::SubscriberExt;
+
+use silamoney::telemetry::otel_layer;
+use silamoney::*;
+
+mod common;
+use common::{sign, start_gateway, Key};
+
+const APP_HANDLE: &str = "telemetry_app";
+
+#[tokio::test]
+async fn exports_a_client_span_per_call_without_message_bodies() {
+    let _gateway = start_gateway(APP_HANDLE, &Key::seeded(2));
+
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = tracing_subscriber::registry().with(otel_layer(provider.tracer("silamoney")));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let message = HeaderMessage::from(CheckHandleMessageParams {
+        sila_handle: "traced".to_string(),
+    });
+    let reference = message.header.reference.clone();
+    let params = sign(&message, Option::None).await;
+    assert!(check_handle(&params).await.unwrap().status == Status::SUCCESS);
+
+    // the HTTP stack may add spans of its own
+    let spans: Vec<_> = exporter
+        .get_finished_spans()
+        .unwrap()
+        .into_iter()
+        .filter(|x| x.name == "check_handle")
+        .collect();
+    assert_eq!(spans.len(), 1);
+
+    let span = &spans[0];
+    assert_eq!(span.span_kind, SpanKind::Client);
+    assert_eq!(span.status, SpanStatus::Ok);
+
+    let attribute = |key: &str| {
+        span.attributes
+            .iter()
+            .find(|x| x.key.as_str() == key)
+            .map(|x| x.value.clone())
+    };
+
+    assert_eq!(attribute("sila.endpoint"), Some(Value::from("check_handle")));
+    assert_eq!(attribute("sila.user_handle"), Some(Value::from("traced")));
+    assert_eq!(attribute("sila.reference"), Some(Value::from(reference)));
+    assert_eq!(attribute("sila.status"), Some(Value::from("SUCCESS")));
+    assert_eq!(attribute("http.status_code"), Some(Value::I64(200)));
+    assert!(attribute("latency_ms").is_some());
+
+    let allowed = [
+        "sila.endpoint",
+        "sila.user_handle",
+        "sila.reference",
+        "sila.status",
+        "sila.response_reference",
+        "http.status_code",
+        "latency_ms",
+        "busy_ns",
+        "idle_ns",
+    ];
+    for x in &span.attributes {
+        assert!(allowed.contains(&x.key.as_str()), "unexpected attribute {}", x.key);
+    }
+}